@@ -0,0 +1,388 @@
+//! Typed views of block devices and partitions.
+//!
+//! Block devices are devices in the `block` subsystem. A whole disk has the devtype `disk` and
+//! its partitions, which are its children, have the devtype `partition`. The kernel reports sizes
+//! and offsets in 512-byte sectors regardless of the device's block size; the views convert them
+//! to bytes.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use libudev::block::BlockDevice;
+//!
+//! let context = libudev::Context::new().unwrap();
+//!
+//! for disk in BlockDevice::disks(&context).unwrap() {
+//!     println!("{:?}: {} bytes, rotational: {:?}", disk.sysname(), disk.size().unwrap_or(0), disk.is_rotational());
+//!
+//!     for partition in disk.partitions().unwrap() {
+//!         println!("  {:?}: {:?} {:?}", partition.number(), partition.fs_type(), partition.fs_uuid());
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+
+use std::ffi::OsStr;
+use std::ops::Deref;
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::{Enumerator, SortOrder};
+
+
+/// The size of the sectors in which the kernel reports sizes and offsets of block devices.
+pub const SECTOR_SIZE: u64 = 512;
+
+
+/// A block device: a whole disk or a partition.
+///
+/// A `BlockDevice` wraps a device in the `block` subsystem and provides typed access to its
+/// attributes and to the properties set by udev's `blkid` builtin. It dereferences to the
+/// underlying `Device`.
+#[derive(Clone)]
+pub struct BlockDevice {
+    device: Device,
+}
+
+impl BlockDevice {
+    /// Creates a block device view of `device`.
+    ///
+    /// Returns `None` if `device` isn't a block device.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if device.subsystem() == Some(OsStr::new("block")) {
+            Some(BlockDevice { device })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the whole disks in the system, ordered by sysname.
+    pub fn disks(context: &Context) -> ::Result<Vec<BlockDevice>> {
+        let mut enumerator = Enumerator::new(context)?;
+
+        enumerator.match_subsystem("block")?;
+        enumerator.match_property("DEVTYPE", "disk")?;
+
+        let devices = enumerator.scan_devices()?.sorted(SortOrder::Sysnum);
+
+        Ok(devices.into_iter().filter_map(BlockDevice::from_device).collect())
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the underlying device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Checks whether the device is a whole disk.
+    pub fn is_disk(&self) -> bool {
+        self.device.devtype() == Some(OsStr::new("disk"))
+    }
+
+    /// Checks whether the device is a partition.
+    pub fn is_partition(&self) -> bool {
+        self.device.devtype() == Some(OsStr::new("partition"))
+    }
+
+    /// Returns a partition view of the device, or `None` if it isn't a partition.
+    pub fn as_partition(&self) -> Option<Partition> {
+        Partition::from_device(self.device.clone())
+    }
+
+    /// Returns the size of the device in 512-byte sectors.
+    pub fn sectors(&self) -> Option<u64> {
+        ::util::parse_decimal(self.device.attribute_value("size"))
+    }
+
+    /// Returns the size of the device in bytes.
+    ///
+    /// Returns `None` if the size in bytes doesn't fit in a `u64`.
+    pub fn size(&self) -> Option<u64> {
+        self.sectors().and_then(|sectors| sectors.checked_mul(SECTOR_SIZE))
+    }
+
+    /// Returns the smallest unit the device can address, in bytes.
+    ///
+    /// For a partition, this is the logical block size of its disk.
+    pub fn logical_block_size(&self) -> Option<u64> {
+        self.queue_attribute("logical_block_size", ::util::parse_decimal)
+    }
+
+    /// Returns the smallest unit the device can write without a read-modify-write cycle, in bytes.
+    ///
+    /// For a partition, this is the physical block size of its disk.
+    pub fn physical_block_size(&self) -> Option<u64> {
+        self.queue_attribute("physical_block_size", ::util::parse_decimal)
+    }
+
+    /// Checks whether the device is a rotational disk, as opposed to solid-state storage.
+    ///
+    /// For a partition, this is the rotational flag of its disk.
+    pub fn is_rotational(&self) -> Option<bool> {
+        self.queue_attribute("rotational", ::util::parse_flag)
+    }
+
+    /// Checks whether the device's media is removable.
+    ///
+    /// For a partition, this is the removable flag of its disk.
+    pub fn is_removable(&self) -> Option<bool> {
+        if self.is_disk() {
+            ::util::parse_flag(self.device.attribute_value("removable"))
+        }
+        else {
+            self.disk().and_then(|disk| ::util::parse_flag(disk.device.attribute_value("removable")))
+        }
+    }
+
+    /// Checks whether the device is read-only.
+    pub fn is_read_only(&self) -> Option<bool> {
+        ::util::parse_flag(self.device.attribute_value("ro"))
+    }
+
+    /// Returns the whole disk the device belongs to: the device itself if it's a disk, or the
+    /// disk that contains it if it's a partition.
+    pub fn disk(&self) -> Option<BlockDevice> {
+        if self.is_disk() {
+            Some(self.clone())
+        }
+        else {
+            self.device.parent_with_subsystem_devtype("block", "disk").map(|device| BlockDevice { device })
+        }
+    }
+
+    /// Returns the partitions of the disk, ordered by sysname.
+    ///
+    /// Returns an empty list for a partition or a disk without a partition table.
+    pub fn partitions(&self) -> ::Result<Vec<Partition>> {
+        if !self.is_disk() {
+            return Ok(Vec::new());
+        }
+
+        let children = ::device::children(&self.device, "block")?;
+
+        Ok(children.into_iter().filter_map(Partition::from_device).collect())
+    }
+
+    /// Returns the type of the filesystem or other content on the device (`ID_FS_TYPE`), e.g.,
+    /// `ext4`, `vfat`, `crypto_LUKS`, or `swap`.
+    pub fn fs_type(&self) -> Option<&str> {
+        self.property("ID_FS_TYPE")
+    }
+
+    /// Returns the version of the filesystem format (`ID_FS_VERSION`).
+    pub fn fs_version(&self) -> Option<&str> {
+        self.property("ID_FS_VERSION")
+    }
+
+    /// Returns the UUID of the filesystem (`ID_FS_UUID`).
+    pub fn fs_uuid(&self) -> Option<&str> {
+        self.property("ID_FS_UUID")
+    }
+
+    /// Returns the label of the filesystem (`ID_FS_LABEL`).
+    pub fn fs_label(&self) -> Option<&str> {
+        self.property("ID_FS_LABEL")
+    }
+
+    /// Returns what the content of the device is used for (`ID_FS_USAGE`).
+    pub fn fs_usage(&self) -> Option<FsUsage> {
+        self.property("ID_FS_USAGE").map(FsUsage::from_str)
+    }
+
+    /// Returns the type of the partition table on the device (`ID_PART_TABLE_TYPE`), e.g., `gpt`
+    /// or `dos`.
+    pub fn partition_table_type(&self) -> Option<&str> {
+        self.property("ID_PART_TABLE_TYPE")
+    }
+
+    /// Returns the UUID of the partition table on the device (`ID_PART_TABLE_UUID`).
+    pub fn partition_table_uuid(&self) -> Option<&str> {
+        self.property("ID_PART_TABLE_UUID")
+    }
+
+    fn property(&self, property: &str) -> Option<&str> {
+        ::util::value_to_str(self.device.property_value(property)).filter(|value| !value.is_empty())
+    }
+
+    /// Reads and parses an attribute of the request queue, which only whole disks have.
+    fn queue_attribute<T, F: FnOnce(Option<&OsStr>) -> Option<T>>(&self, attribute: &str, parse: F) -> Option<T> {
+        let attribute = format!("queue/{}", attribute);
+
+        if self.is_disk() {
+            parse(self.device.attribute_value(attribute))
+        }
+        else {
+            self.disk().and_then(|disk| parse(disk.device.attribute_value(attribute)))
+        }
+    }
+}
+
+impl Deref for BlockDevice {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for BlockDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BlockDevice")
+            .field("syspath", &self.device.syspath())
+            .field("devtype", &self.device.devtype())
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+
+/// A partition of a disk.
+///
+/// A `Partition` wraps a block device with the devtype `partition`. It dereferences to a
+/// `BlockDevice`, which provides its size and filesystem properties.
+#[derive(Clone)]
+pub struct Partition {
+    block: BlockDevice,
+}
+
+impl Partition {
+    /// Creates a partition view of `device`.
+    ///
+    /// Returns `None` if `device` isn't a partition.
+    pub fn from_device(device: Device) -> Option<Self> {
+        BlockDevice::from_device(device).filter(|block| block.is_partition()).map(|block| Partition { block })
+    }
+
+    /// Returns the block device view of the partition.
+    pub fn block_device(&self) -> &BlockDevice {
+        &self.block
+    }
+
+    /// Consumes the view and returns the underlying device.
+    pub fn into_device(self) -> Device {
+        self.block.device
+    }
+
+    /// Returns the number of the partition in its partition table (`partition`).
+    pub fn number(&self) -> Option<u32> {
+        ::util::parse_decimal(self.block.device.attribute_value("partition"))
+    }
+
+    /// Returns the offset of the partition from the start of its disk in 512-byte sectors.
+    pub fn start_sector(&self) -> Option<u64> {
+        ::util::parse_decimal(self.block.device.attribute_value("start"))
+    }
+
+    /// Returns the offset of the partition from the start of its disk in bytes.
+    ///
+    /// Returns `None` if the offset in bytes doesn't fit in a `u64`.
+    pub fn start(&self) -> Option<u64> {
+        self.start_sector().and_then(|sectors| sectors.checked_mul(SECTOR_SIZE))
+    }
+
+    /// Returns the disk that contains the partition.
+    pub fn parent_disk(&self) -> Option<BlockDevice> {
+        self.block.disk()
+    }
+
+    /// Returns the scheme of the partition table that defines the partition
+    /// (`ID_PART_ENTRY_SCHEME`), e.g., `gpt` or `dos`.
+    pub fn entry_scheme(&self) -> Option<&str> {
+        self.block.property("ID_PART_ENTRY_SCHEME")
+    }
+
+    /// Returns the type of the partition (`ID_PART_ENTRY_TYPE`): a GUID for GPT partitions or a
+    /// hexadecimal type code, such as `0x83`, for DOS partitions.
+    pub fn entry_type(&self) -> Option<&str> {
+        self.block.property("ID_PART_ENTRY_TYPE")
+    }
+
+    /// Returns the unique identifier of the partition in its partition table
+    /// (`ID_PART_ENTRY_UUID`).
+    pub fn entry_uuid(&self) -> Option<&str> {
+        self.block.property("ID_PART_ENTRY_UUID")
+    }
+
+    /// Returns the name of the partition in its partition table (`ID_PART_ENTRY_NAME`).
+    pub fn entry_name(&self) -> Option<&str> {
+        self.block.property("ID_PART_ENTRY_NAME")
+    }
+
+    /// Returns the flags of the partition in its partition table (`ID_PART_ENTRY_FLAGS`).
+    pub fn entry_flags(&self) -> Option<u64> {
+        ::util::parse_hex(self.block.device.property_value("ID_PART_ENTRY_FLAGS"))
+    }
+
+    /// Returns the number of the partition as recorded by `blkid` (`ID_PART_ENTRY_NUMBER`).
+    pub fn entry_number(&self) -> Option<u32> {
+        ::util::parse_decimal(self.block.device.property_value("ID_PART_ENTRY_NUMBER"))
+    }
+
+    /// Returns the offset of the partition as recorded by `blkid`, in 512-byte sectors
+    /// (`ID_PART_ENTRY_OFFSET`).
+    pub fn entry_offset(&self) -> Option<u64> {
+        ::util::parse_decimal(self.block.device.property_value("ID_PART_ENTRY_OFFSET"))
+    }
+
+    /// Returns the size of the partition as recorded by `blkid`, in 512-byte sectors
+    /// (`ID_PART_ENTRY_SIZE`).
+    pub fn entry_size(&self) -> Option<u64> {
+        ::util::parse_decimal(self.block.device.property_value("ID_PART_ENTRY_SIZE"))
+    }
+}
+
+impl Deref for Partition {
+    type Target = BlockDevice;
+
+    fn deref(&self) -> &BlockDevice {
+        &self.block
+    }
+}
+
+impl fmt::Debug for Partition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Partition")
+            .field("syspath", &self.block.device.syspath())
+            .field("number", &self.number())
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+
+/// What the content of a block device is used for, as reported by `blkid`.
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub enum FsUsage {
+    /// A mountable filesystem.
+    Filesystem,
+
+    /// A member of a RAID array or volume group.
+    Raid,
+
+    /// Encrypted data, such as a LUKS volume.
+    Crypto,
+
+    /// Other content, such as swap space.
+    Other,
+
+    /// A usage that this crate doesn't know about.
+    Unknown(String),
+}
+
+impl FsUsage {
+    fn from_str(value: &str) -> FsUsage {
+        match value {
+            "filesystem" => FsUsage::Filesystem,
+            "raid" => FsUsage::Raid,
+            "crypto" => FsUsage::Crypto,
+            "other" => FsUsage::Other,
+            _ => FsUsage::Unknown(value.to_owned()),
+        }
+    }
+}
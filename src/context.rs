@@ -1,5 +1,13 @@
 use ::handle::Handle;
 
+
+/// Creates a context that shares the given libudev context.
+pub unsafe fn from_raw(udev: *mut ::ffi::udev) -> Context {
+    ::ffi::udev_ref(udev);
+
+    Context { udev }
+}
+
 /// A libudev context. Contexts may not be sent or shared between threads. The `libudev(3)` manpage
 /// says:
 ///
@@ -1,14 +1,18 @@
+use std::ptr;
 use std::str;
 
 use std::ffi::{CStr, OsStr};
 use std::marker::PhantomData;
 use std::path::Path;
 use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
 use libc::{c_char, dev_t};
 
 use ::context::Context;
+use ::enumerator::{Enumerator, SortOrder};
 use ::handle::Handle;
+use ::trigger::{Action, Uuid};
 
 
 pub unsafe fn from_raw(device: *mut ::ffi::udev_device) -> Device {
@@ -17,12 +21,65 @@ pub unsafe fn from_raw(device: *mut ::ffi::udev_device) -> Device {
     Device { device: device }
 }
 
+/// Wraps a parent device returned by libudev, which is owned by its child.
+unsafe fn parent_from_ptr(ptr: *mut ::ffi::udev_device) -> Option<Device> {
+    if ptr.is_null() {
+        return None;
+    }
+
+    ::ffi::udev_device_ref(ptr);
+
+    Some(from_raw(ptr))
+}
+
+/// Returns the devices below `device` in the hierarchy that belong to the given subsystem,
+/// ordered by sysname and sysnum.
+pub fn descendants(device: &Device, subsystem: &str) -> ::Result<Vec<Device>> {
+    let context = device.context();
+    let mut enumerator = Enumerator::new(&context)?;
+
+    enumerator.match_parent(device)?;
+    enumerator.match_subsystem(subsystem)?;
+
+    let mut devices = enumerator.scan_devices()?.sorted(SortOrder::Sysnum);
+    devices.retain(|d| d.syspath() != device.syspath());
+
+    Ok(devices)
+}
+
+/// Returns the direct children of `device` that belong to the given subsystem, ordered by sysname
+/// and sysnum.
+pub fn children(device: &Device, subsystem: &str) -> ::Result<Vec<Device>> {
+    let mut devices = descendants(device, subsystem)?;
+    devices.retain(|d| d.parent().is_some_and(|p| p.syspath() == device.syspath()));
+
+    Ok(devices)
+}
+
+/// Creates a device for a syspath, reporting a failure with the `errno` set by libudev.
+pub unsafe fn from_syspath_raw(udev: *mut ::ffi::udev, syspath: *const c_char) -> ::Result<Device> {
+    let device = ::ffi::udev_device_new_from_syspath(udev, syspath);
+
+    if device.is_null() {
+        return Err(::error::last_os_error());
+    }
+
+    Ok(from_raw(device))
+}
+
 
 /// A structure that provides access to sysfs/kernel devices.
 pub struct Device {
     device: *mut ::ffi::udev_device,
 }
 
+impl Clone for Device {
+    /// Increments the reference count of the `libudev` device.
+    fn clone(&self) -> Self {
+        unsafe { from_raw(::ffi::udev_device_ref(self.device)) }
+    }
+}
+
 impl Drop for Device {
     fn drop(&mut self) {
         unsafe {
@@ -47,7 +104,7 @@ impl Device {
     /// The `syspath` parameter should be a path to the device file within the `sysfs` file system,
     /// e.g., `/sys/devices/virtual/tty/tty0`.
     pub fn from_syspath(context: &Context, syspath: &Path) -> ::Result<Self> {
-        let syspath = try!(::util::os_str_to_cstring(syspath));
+        let syspath = ::util::os_str_to_cstring(syspath)?;
 
         Ok(unsafe {
             from_raw(try_alloc!(
@@ -70,6 +127,25 @@ impl Device {
         }
     }
 
+    /// Returns the time that has elapsed since udev initialized the device.
+    ///
+    /// Returns `None` if the device hasn't been initialized by udev or if udev doesn't record an
+    /// initialization time for it.
+    pub fn usec_since_initialized(&self) -> Option<Duration> {
+        match unsafe { ::ffi::udev_device_get_usec_since_initialized(self.device) } {
+            0 => None,
+            usec => Some(Duration::new(usec / 1_000_000, (usec % 1_000_000) as u32 * 1000)),
+        }
+    }
+
+    /// Estimates the time at which udev initialized the device.
+    ///
+    /// The estimate is computed by subtracting `usec_since_initialized()` from the current system
+    /// time, so it moves if the system clock is adjusted.
+    pub fn initialized_at(&self) -> Option<SystemTime> {
+        self.usec_since_initialized().and_then(|elapsed| SystemTime::now().checked_sub(elapsed))
+    }
+
     /// Gets the device's major/minor number.
     pub fn devnum(&self) -> Option<dev_t> {
         match unsafe { ::ffi::udev_device_get_devnum(self.device) } {
@@ -125,6 +201,31 @@ impl Device {
         }
     }
 
+    /// Returns the closest ancestor of the device that belongs to the given subsystem.
+    pub fn parent_with_subsystem<T: AsRef<OsStr>>(&self, subsystem: T) -> Option<Device> {
+        let subsystem = ::util::os_str_to_cstring(subsystem).ok()?;
+
+        unsafe {
+            parent_from_ptr(::ffi::udev_device_get_parent_with_subsystem_devtype(self.device, subsystem.as_ptr(), ptr::null()))
+        }
+    }
+
+    /// Returns the closest ancestor of the device that belongs to the given subsystem and has the
+    /// given devtype.
+    pub fn parent_with_subsystem_devtype<T: AsRef<OsStr>, U: AsRef<OsStr>>(&self, subsystem: T, devtype: U) -> Option<Device> {
+        let subsystem = ::util::os_str_to_cstring(subsystem).ok()?;
+        let devtype = ::util::os_str_to_cstring(devtype).ok()?;
+
+        unsafe {
+            parent_from_ptr(::ffi::udev_device_get_parent_with_subsystem_devtype(self.device, subsystem.as_ptr(), devtype.as_ptr()))
+        }
+    }
+
+    /// Returns the libudev context that the device belongs to.
+    pub fn context(&self) -> Context {
+        unsafe { ::context::from_raw(::ffi::udev_device_get_udev(self.device)) }
+    }
+
     /// Returns the subsystem name of the device.
     ///
     /// The subsystem name is a string that indicates which kernel subsystem the device belongs to.
@@ -205,14 +306,57 @@ impl Device {
 
     /// Sets the value of a device attribute.
     pub fn set_attribute_value<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, attribute: T, value: U) -> ::Result<()> {
-        let attribute = try!(::util::os_str_to_cstring(attribute));
-        let value = try!(::util::os_str_to_cstring(value));
+        let attribute = ::util::os_str_to_cstring(attribute)?;
+        let value = ::util::os_str_to_cstring(value)?;
 
         ::util::errno_to_result(unsafe {
             ::ffi::udev_device_set_sysattr_value(self.device, attribute.as_ptr(), value.as_ptr() as *mut c_char)
         })
     }
 
+    /// Checks whether udev has attached the given tag to the device.
+    pub fn has_tag<T: AsRef<OsStr>>(&self, tag: T) -> bool {
+        match ::util::os_str_to_cstring(tag) {
+            Ok(tag) => unsafe { ::ffi::udev_device_has_tag(self.device, tag.as_ptr()) > 0 },
+            Err(_) => false,
+        }
+    }
+
+    /// Returns an iterator over the tags that udev has attached to the device.
+    pub fn tags(&self) -> Tags<'_> {
+        Tags {
+            _device: PhantomData,
+            entry: unsafe { ::ffi::udev_device_get_tags_list_entry(self.device) },
+        }
+    }
+
+    /// Returns an iterator over the symlinks that udev has created for the device node, such as
+    /// `/dev/disk/by-id/...` or `/dev/serial/by-path/...`.
+    pub fn devlinks(&self) -> Devlinks<'_> {
+        Devlinks {
+            _device: PhantomData,
+            entry: unsafe { ::ffi::udev_device_get_devlinks_list_entry(self.device) },
+        }
+    }
+
+    /// Requests a synthetic uevent for the device from the kernel.
+    ///
+    /// This writes the action and a random UUID to the device's `uevent` attribute. The kernel
+    /// emits an event with the requested action, and `udevd` processes it as if it was a real
+    /// event. The event carries the returned UUID in its `SYNTH_UUID` property, which can be used
+    /// to recognize the event with a `Monitor`. Triggering a `change` event is the usual way to
+    /// re-run the udev rules for a device.
+    ///
+    /// Writing to `uevent` requires root privileges. To trigger events for many devices and wait
+    /// for them to be processed, see `Trigger`.
+    pub fn trigger(&mut self, action: Action) -> ::Result<Uuid> {
+        let uuid = Uuid::new_v4()?;
+
+        self.set_attribute_value("uevent", format!("{} {}", action, uuid))?;
+
+        Ok(uuid)
+    }
+
     /// Returns an iterator over the device's properties.
     ///
     /// ## Example
@@ -257,6 +401,88 @@ impl Device {
 }
 
 
+/// A device that is only read from sysfs when it's needed.
+///
+/// Creating a `Device` reads the device's `uevent` file and other information from sysfs. A
+/// `DeviceRef` only holds the device's syspath until `device()` is called, which makes it cheap to
+/// pass around large numbers of devices that are mostly inspected by path.
+///
+/// ## Example
+///
+/// ```no_run
+/// use libudev::DeviceRef;
+///
+/// let context = libudev::Context::new().unwrap();
+/// let mut enumerator = libudev::Enumerator::new(&context).unwrap();
+/// enumerator.match_subsystem("block").unwrap();
+///
+/// for syspath in enumerator.scan_syspaths().unwrap() {
+///     let mut device = DeviceRef::new(&context, syspath);
+///
+///     if device.sysname().is_some_and(|name| name.to_string_lossy().starts_with("sd")) {
+///         println!("{:?}", device.device().unwrap().devnode());
+///     }
+/// }
+/// ```
+pub struct DeviceRef<'a> {
+    context: &'a Context,
+    syspath: &'a Path,
+    device: Option<Device>,
+}
+
+impl<'a> DeviceRef<'a> {
+    /// Creates a reference to the device with the given syspath. Sysfs is not accessed.
+    pub fn new(context: &'a Context, syspath: &'a Path) -> Self {
+        DeviceRef {
+            context,
+            syspath,
+            device: None,
+        }
+    }
+
+    /// Returns the syspath of the device.
+    pub fn syspath(&self) -> &'a Path {
+        self.syspath
+    }
+
+    /// Returns the kernel device name, which is the last component of the syspath.
+    pub fn sysname(&self) -> Option<&'a OsStr> {
+        self.syspath.file_name()
+    }
+
+    /// Checks whether the device has already been read from sysfs.
+    pub fn is_loaded(&self) -> bool {
+        self.device.is_some()
+    }
+
+    /// Returns the device, reading it from sysfs on first use.
+    ///
+    /// If the device can't be read, the error carries the `errno` reported by libudev, e.g.,
+    /// `ENODEV` if the device has been removed. The device is read again on the next call.
+    pub fn device(&mut self) -> ::Result<&Device> {
+        if self.device.is_none() {
+            self.device = Some(self.load()?);
+        }
+
+        Ok(self.device.as_ref().unwrap())
+    }
+
+    /// Returns the device, reading it from sysfs if it hasn't been read yet.
+    pub fn into_device(self) -> ::Result<Device> {
+        match self.device {
+            Some(device) => Ok(device),
+            None => self.load(),
+        }
+    }
+
+    fn load(&self) -> ::Result<Device> {
+        let syspath = ::util::os_str_to_cstring(self.syspath)?;
+
+        unsafe { from_syspath_raw(self.context.as_ptr(), syspath.as_ptr()) }
+    }
+}
+
+
 /// Iterator over a device's properties.
 pub struct Properties<'a> {
     _device: PhantomData<&'a Device>,
@@ -356,3 +582,63 @@ impl<'a> Attribute<'a> {
         self.device.attribute_value(self.name)
     }
 }
+
+
+/// Iterator over a device's tags.
+pub struct Tags<'a> {
+    _device: PhantomData<&'a Device>,
+    entry: *mut ::ffi::udev_list_entry,
+}
+
+impl<'a> Iterator for Tags<'a> {
+    type Item = &'a OsStr;
+
+    fn next(&mut self) -> Option<&'a OsStr> {
+        if !self.entry.is_null() {
+            unsafe {
+                let tag = ::util::ptr_to_os_str_unchecked(::ffi::udev_list_entry_get_name(self.entry));
+
+                self.entry = ::ffi::udev_list_entry_get_next(self.entry);
+
+                Some(tag)
+            }
+        }
+        else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+
+/// Iterator over the symlinks to a device's node.
+pub struct Devlinks<'a> {
+    _device: PhantomData<&'a Device>,
+    entry: *mut ::ffi::udev_list_entry,
+}
+
+impl<'a> Iterator for Devlinks<'a> {
+    type Item = &'a Path;
+
+    fn next(&mut self) -> Option<&'a Path> {
+        if !self.entry.is_null() {
+            unsafe {
+                let devlink = Path::new(::util::ptr_to_os_str_unchecked(::ffi::udev_list_entry_get_name(self.entry)));
+
+                self.entry = ::ffi::udev_list_entry_get_next(self.entry);
+
+                Some(devlink)
+            }
+        }
+        else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
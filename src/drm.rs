@@ -0,0 +1,610 @@
+//! Typed views of graphics cards and display connectors.
+//!
+//! The `drm` subsystem contains three kinds of devices for each graphics card:
+//!
+//! * a primary node, such as `card0` with the device node `/dev/dri/card0`, used for mode setting,
+//! * a render node, such as `renderD128`, used for unprivileged rendering,
+//! * a connector for each display output, such as `card0-HDMI-A-1`, which has no device node.
+//!
+//! When a display is connected or disconnected, the kernel sends a `change` event with the
+//! property `HOTPLUG=1` for the card.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use libudev::drm::{self, ConnectorStatus, DrmCard};
+//!
+//! let context = libudev::Context::new().unwrap();
+//!
+//! for card in DrmCard::cards(&context).unwrap() {
+//!     let render_node = card.render_node().unwrap();
+//!     println!("{:?} render node: {:?}", card.primary_node(), render_node.as_ref().and_then(|node| node.devnode()));
+//!
+//!     for connector in card.connectors().unwrap() {
+//!         if connector.status() == Some(ConnectorStatus::Connected) {
+//!             let edid = connector.edid();
+//!             println!("  {:?}: {:?}", connector.name(), edid.and_then(|edid| edid.preferred_mode()));
+//!         }
+//!     }
+//! }
+//!
+//! let mut socket = drm::monitor(&context).unwrap();
+//!
+//! // Wait for the socket to become readable with poll(), then:
+//! for hotplug in drm::hotplug_events(&mut socket) {
+//!     println!("hotplug on {:?}", hotplug.card().sysname());
+//! }
+//! ```
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+
+use std::ffi::OsStr;
+use std::ops::Deref;
+use std::path::Path;
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::{Enumerator, SortOrder};
+use ::monitor::{Monitor, MonitorSocket};
+
+
+/// Creates a monitor socket that receives events for graphics devices.
+pub fn monitor(context: &Context) -> ::Result<MonitorSocket> {
+    let mut monitor = Monitor::new(context)?;
+
+    monitor.match_subsystem("drm")?;
+
+    monitor.listen()
+}
+
+/// Returns an iterator over the hotplug events that are available on `socket`.
+///
+/// Other events are skipped. Like `MonitorSocket::receive_event()`, the iterator doesn't block:
+/// it ends when no more events are available. Use `monitor()` to create a socket that only
+/// receives graphics events.
+pub fn hotplug_events(socket: &mut MonitorSocket) -> HotplugEvents<'_> {
+    HotplugEvents { socket }
+}
+
+
+/// A graphics card, represented by its primary node.
+///
+/// A `DrmCard` wraps a `card*` device in the `drm` subsystem. It dereferences to the underlying
+/// `Device`.
+#[derive(Clone)]
+pub struct DrmCard {
+    device: Device,
+}
+
+impl DrmCard {
+    /// Creates a card view of `device`.
+    ///
+    /// Returns `None` if `device` isn't the primary node of a graphics card.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if is_drm(&device) && card_index(&device).is_some() {
+            Some(DrmCard { device })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the graphics cards in the system, ordered by index.
+    pub fn cards(context: &Context) -> ::Result<Vec<DrmCard>> {
+        let mut enumerator = Enumerator::new(context)?;
+
+        enumerator.match_subsystem("drm")?;
+        enumerator.match_sysname("card[0-9]*")?;
+
+        let devices = enumerator.scan_devices()?.sorted(SortOrder::Sysnum);
+
+        Ok(devices.into_iter().filter_map(DrmCard::from_device).collect())
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the underlying device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Returns the index of the card, e.g., 0 for `card0`.
+    pub fn index(&self) -> Option<u32> {
+        card_index(&self.device)
+    }
+
+    /// Returns the primary node of the card, e.g., `/dev/dri/card0`.
+    pub fn primary_node(&self) -> Option<&Path> {
+        self.device.devnode()
+    }
+
+    /// Returns the render node of the card, e.g., `/dev/dri/renderD128`.
+    ///
+    /// The render node is found among the other `drm` devices of the card's parent. Returns
+    /// `None` if the driver doesn't support render nodes.
+    pub fn render_node(&self) -> ::Result<Option<Device>> {
+        let parent = match self.device.parent() {
+            Some(parent) => parent,
+            None => return Ok(None),
+        };
+
+        let siblings = ::device::children(&parent, "drm")?;
+
+        Ok(siblings.into_iter().find(|device| ::util::value_to_str(device.sysname()).is_some_and(|sysname| sysname.starts_with("renderD"))))
+    }
+
+    /// Returns the connectors of the card, ordered by sysname.
+    pub fn connectors(&self) -> ::Result<Vec<Connector>> {
+        let children = ::device::children(&self.device, "drm")?;
+
+        Ok(children.into_iter().filter_map(Connector::from_device).collect())
+    }
+
+    /// Returns the device that provides the card, such as a PCI device or a platform device.
+    pub fn hardware(&self) -> Option<Device> {
+        self.device.parent()
+    }
+
+    /// Returns the name of the card's driver, e.g., `i915`, `amdgpu`, or `vc4`.
+    pub fn driver(&self) -> Option<String> {
+        self.hardware().and_then(|hardware| hardware.driver().map(|driver| driver.to_string_lossy().into_owned()))
+    }
+}
+
+impl Deref for DrmCard {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for DrmCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DrmCard")
+            .field("syspath", &self.device.syspath())
+            .field("primary_node", &self.primary_node())
+            .finish()
+    }
+}
+
+
+/// A display connector of a graphics card.
+///
+/// A `Connector` wraps a `card*-*` device in the `drm` subsystem. It dereferences to the
+/// underlying `Device`.
+#[derive(Clone)]
+pub struct Connector {
+    device: Device,
+}
+
+impl Connector {
+    /// Creates a connector view of `device`.
+    ///
+    /// Returns `None` if `device` isn't a display connector.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if !is_drm(&device) || device.devnode().is_some() {
+            return None;
+        }
+
+        let sysname = ::util::value_to_str(device.sysname())?;
+
+        if sysname.starts_with("card") && sysname.contains('-') {
+            Some(Connector { device })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the underlying device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Returns the name of the connector without the card prefix, e.g., `HDMI-A-1` or `eDP-1`.
+    pub fn name(&self) -> Option<&str> {
+        ::util::value_to_str(self.device.sysname()).and_then(|sysname| sysname.split_once('-')).map(|(_, name)| name)
+    }
+
+    /// Returns the ID of the connector used by the mode setting API (`connector_id`).
+    pub fn connector_id(&self) -> Option<u32> {
+        ::util::parse_decimal(self.device.attribute_value("connector_id"))
+    }
+
+    /// Returns the card that the connector belongs to.
+    pub fn card(&self) -> Option<DrmCard> {
+        self.device.parent().and_then(DrmCard::from_device)
+    }
+
+    /// Returns whether a display is connected (`status`).
+    pub fn status(&self) -> Option<ConnectorStatus> {
+        ::util::value_to_str(self.device.attribute_value("status")).map(ConnectorStatus::from_str)
+    }
+
+    /// Checks whether the connector is driven by a display controller (`enabled`).
+    pub fn is_enabled(&self) -> Option<bool> {
+        match ::util::value_to_str(self.device.attribute_value("enabled")) {
+            Some("enabled") => Some(true),
+            Some("disabled") => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Returns the power state of the connected display (`dpms`).
+    pub fn dpms(&self) -> Option<Dpms> {
+        match ::util::value_to_str(self.device.attribute_value("dpms")) {
+            Some("On") => Some(Dpms::On),
+            Some("Standby") => Some(Dpms::Standby),
+            Some("Suspend") => Some(Dpms::Suspend),
+            Some("Off") => Some(Dpms::Off),
+            _ => None,
+        }
+    }
+
+    /// Returns the modes supported by the connected display (`modes`), e.g., `1920x1080`.
+    pub fn modes(&self) -> Vec<&str> {
+        match ::util::value_to_str(self.device.attribute_value("modes")) {
+            Some(modes) => modes.lines().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Reads the EDID of the connected display from the `edid` attribute.
+    ///
+    /// The EDID is binary, so it's read from sysfs directly rather than through libudev, which
+    /// treats attribute values as strings. The result is empty if no display is connected.
+    pub fn edid_bytes(&self) -> io::Result<Vec<u8>> {
+        match self.device.syspath() {
+            Some(syspath) => fs::read(syspath.join("edid")),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "device has no syspath")),
+        }
+    }
+
+    /// Reads and parses the EDID of the connected display.
+    ///
+    /// Returns `None` if no display is connected or its EDID can't be read or parsed.
+    pub fn edid(&self) -> Option<Edid> {
+        self.edid_bytes().ok().and_then(|bytes| Edid::parse(&bytes).ok())
+    }
+}
+
+impl Deref for Connector {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for Connector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Connector")
+            .field("name", &self.name())
+            .field("status", &self.status())
+            .finish()
+    }
+}
+
+
+/// Whether a display is connected to a connector.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum ConnectorStatus {
+    /// A display is connected.
+    Connected,
+
+    /// No display is connected.
+    Disconnected,
+
+    /// The driver can't tell whether a display is connected.
+    Unknown,
+}
+
+impl ConnectorStatus {
+    fn from_str(value: &str) -> ConnectorStatus {
+        match value {
+            "connected" => ConnectorStatus::Connected,
+            "disconnected" => ConnectorStatus::Disconnected,
+            _ => ConnectorStatus::Unknown,
+        }
+    }
+}
+
+/// The DPMS power state of a display.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Dpms {
+    /// The display is on.
+    On,
+
+    /// The display is blanked but recovers immediately.
+    Standby,
+
+    /// The display is in a lower power state than standby and takes longer to recover.
+    Suspend,
+
+    /// The display is off.
+    Off,
+}
+
+
+/// The basic information from an EDID block: the identity of a display and its preferred mode.
+///
+/// ```
+/// use libudev::drm::Edid;
+///
+/// let mut bytes = vec![0; 128];
+/// bytes[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+/// bytes[8..10].copy_from_slice(&[0x10, 0xac]); // DEL
+/// bytes[10..12].copy_from_slice(&[0x34, 0x12]);
+/// bytes[18..20].copy_from_slice(&[1, 4]);
+///
+/// // 1920x1080 at 60 Hz with a pixel clock of 148.5 MHz.
+/// bytes[54..62].copy_from_slice(&[0x02, 0x3a, 0x80, 0x18, 0x71, 0x38, 0x2d, 0x40]);
+///
+/// let checksum = bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+/// bytes[127] = 0u8.wrapping_sub(checksum);
+///
+/// let edid = Edid::parse(&bytes).unwrap();
+/// let mode = edid.preferred_mode().unwrap();
+///
+/// assert_eq!(edid.manufacturer(), "DEL");
+/// assert_eq!(edid.product_code(), 0x1234);
+/// assert_eq!((mode.width(), mode.height()), (1920, 1080));
+/// assert_eq!(mode.refresh_rate().round(), 60.0);
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Edid {
+    manufacturer: String,
+    product_code: u16,
+    serial_number: u32,
+    week: u8,
+    year: u16,
+    version: (u8, u8),
+    name: Option<String>,
+    serial_string: Option<String>,
+    preferred_mode: Option<Mode>,
+}
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+impl Edid {
+    /// Parses the base block of an EDID. Extension blocks are ignored.
+    pub fn parse(bytes: &[u8]) -> Result<Edid, ParseEdidError> {
+        if bytes.len() < 128 || bytes[..8] != EDID_HEADER {
+            return Err(ParseEdidError);
+        }
+
+        let block = &bytes[..128];
+
+        if block.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0 {
+            return Err(ParseEdidError);
+        }
+
+        let id = u16::from_be_bytes([block[8], block[9]]);
+        let manufacturer = [10, 5, 0].iter().map(|shift| (b'A' - 1 + ((id >> shift) & 0x1f) as u8) as char).collect();
+
+        let mut edid = Edid {
+            manufacturer,
+            product_code: u16::from_le_bytes([block[10], block[11]]),
+            serial_number: u32::from_le_bytes([block[12], block[13], block[14], block[15]]),
+            week: block[16],
+            year: 1990 + block[17] as u16,
+            version: (block[18], block[19]),
+            name: None,
+            serial_string: None,
+            preferred_mode: None,
+        };
+
+        for descriptor in block[54..126].chunks(18) {
+            if descriptor[0] != 0 || descriptor[1] != 0 {
+                if edid.preferred_mode.is_none() {
+                    edid.preferred_mode = Some(Mode::from_timing_descriptor(descriptor));
+                }
+
+                continue;
+            }
+
+            match descriptor[3] {
+                0xfc => edid.name = Some(descriptor_text(descriptor)),
+                0xff => edid.serial_string = Some(descriptor_text(descriptor)),
+                _ => {},
+            }
+        }
+
+        Ok(edid)
+    }
+
+    /// Returns the three-letter PNP ID of the manufacturer, e.g., `DEL` or `SAM`.
+    pub fn manufacturer(&self) -> &str {
+        &self.manufacturer
+    }
+
+    /// Returns the manufacturer's product code.
+    pub fn product_code(&self) -> u16 {
+        self.product_code
+    }
+
+    /// Returns the numeric serial number, or 0 if the display doesn't report one.
+    pub fn serial_number(&self) -> u32 {
+        self.serial_number
+    }
+
+    /// Returns the serial number from the display's serial number descriptor.
+    pub fn serial_string(&self) -> Option<&str> {
+        self.serial_string.as_deref()
+    }
+
+    /// Returns the name from the display's name descriptor.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the week of manufacture, from 1 to 54, or 0 if it isn't specified.
+    pub fn week(&self) -> u8 {
+        self.week
+    }
+
+    /// Returns the year of manufacture.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Returns the EDID version and revision, e.g., `(1, 4)`.
+    pub fn version(&self) -> (u8, u8) {
+        self.version
+    }
+
+    /// Returns the preferred mode of the display, from the first detailed timing descriptor.
+    pub fn preferred_mode(&self) -> Option<Mode> {
+        self.preferred_mode
+    }
+}
+
+fn descriptor_text(descriptor: &[u8]) -> String {
+    let text = &descriptor[5..18];
+    let end = text.iter().position(|&b| b == b'\n').unwrap_or(text.len());
+
+    String::from_utf8_lossy(&text[..end]).trim_end().to_owned()
+}
+
+/// An error returned when bytes aren't a valid EDID.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct ParseEdidError;
+
+impl fmt::Display for ParseEdidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid EDID")
+    }
+}
+
+impl error::Error for ParseEdidError {}
+
+
+/// A display mode from a detailed timing descriptor.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Mode {
+    width: u16,
+    height: u16,
+    horizontal_total: u16,
+    vertical_total: u16,
+    pixel_clock_khz: u32,
+}
+
+impl Mode {
+    fn from_timing_descriptor(d: &[u8]) -> Mode {
+        let width = d[2] as u16 | ((d[4] as u16 & 0xf0) << 4);
+        let horizontal_blanking = d[3] as u16 | ((d[4] as u16 & 0x0f) << 8);
+        let height = d[5] as u16 | ((d[7] as u16 & 0xf0) << 4);
+        let vertical_blanking = d[6] as u16 | ((d[7] as u16 & 0x0f) << 8);
+
+        Mode {
+            width,
+            height,
+            horizontal_total: width + horizontal_blanking,
+            vertical_total: height + vertical_blanking,
+            pixel_clock_khz: u16::from_le_bytes([d[0], d[1]]) as u32 * 10,
+        }
+    }
+
+    /// Returns the number of visible pixels per line.
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Returns the number of visible lines.
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Returns the pixel clock in kHz.
+    pub fn pixel_clock_khz(&self) -> u32 {
+        self.pixel_clock_khz
+    }
+
+    /// Returns the refresh rate in Hz.
+    pub fn refresh_rate(&self) -> f64 {
+        let total = self.horizontal_total as f64 * self.vertical_total as f64;
+
+        if total == 0.0 {
+            0.0
+        }
+        else {
+            self.pixel_clock_khz as f64 * 1000.0 / total
+        }
+    }
+}
+
+
+/// A hotplug event for a graphics card.
+pub struct Hotplug {
+    card: DrmCard,
+    connector_id: Option<u32>,
+}
+
+impl Hotplug {
+    /// Returns the card that reported the event. Its connectors reflect the new state.
+    pub fn card(&self) -> &DrmCard {
+        &self.card
+    }
+
+    /// Returns the ID of the connector that changed, if the kernel reported it.
+    pub fn connector_id(&self) -> Option<u32> {
+        self.connector_id
+    }
+}
+
+impl fmt::Debug for Hotplug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Hotplug")
+            .field("card", &self.card)
+            .field("connector_id", &self.connector_id)
+            .finish()
+    }
+}
+
+/// Iterator over the hotplug events that are available on a monitor socket.
+pub struct HotplugEvents<'a> {
+    socket: &'a mut MonitorSocket,
+}
+
+impl<'a> Iterator for HotplugEvents<'a> {
+    type Item = Hotplug;
+
+    fn next(&mut self) -> Option<Hotplug> {
+        while let Some(event) = self.socket.receive_event() {
+            if ::util::parse_flag(event.property_value("HOTPLUG")) != Some(true) {
+                continue;
+            }
+
+            if let Some(card) = DrmCard::from_device(event.device().clone()) {
+                return Some(Hotplug {
+                    connector_id: ::util::parse_decimal(event.property_value("CONNECTOR")),
+                    card,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+
+fn is_drm(device: &Device) -> bool {
+    device.subsystem() == Some(OsStr::new("drm"))
+}
+
+/// Parses the index of a primary node from its sysname, such as `card0`.
+fn card_index(device: &Device) -> Option<u32> {
+    ::util::value_to_str(device.sysname())?.strip_prefix("card")?.parse().ok()
+}
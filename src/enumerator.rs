@@ -1,10 +1,19 @@
+use std::error;
+use std::fmt;
+use std::fs;
+use std::slice;
+
+use std::cmp::Ordering;
 use std::ffi::OsStr;
-use std::marker::PhantomData;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use std::os::unix::prelude::*;
 
 use ::context::Context;
 use ::device::Device;
 use ::handle::Handle;
+use ::query::DeviceQuery;
 
 
 /// An enumeration context.
@@ -12,8 +21,21 @@ use ::handle::Handle;
 /// An Enumerator scans `/sys` for devices matching its filters. Filters are added to an Enumerator
 /// by calling its `match_*` and `nomatch_*` methods. After the filters are setup, the
 /// `scan_devices()` method finds devices in `/sys` that match the filters.
+///
+/// Filters of the same kind don't all combine the same way. libudev matches a device if it
+/// belongs to *any* of the subsystems, has *any* of the sysnames, and has *any* of the property
+/// values, but requires *all* of the attribute values and *all* of the tags. Excluded subsystems,
+/// sysnames, and attribute values exclude a device if any of them matches. A device must also be
+/// in the subtree of the parent, of which there is at most one. Devices added with `add_syspath()`
+/// are always included.
+///
+/// Most filters are implemented by libudev. Excluded sysnames and filters on initialization time
+/// are applied by this library to the devices returned by libudev. `describe()` returns all of the
+/// filters that have been added.
 pub struct Enumerator {
     enumerator: *mut ::ffi::udev_enumerate,
+    query: DeviceQuery,
+    subsystems: Vec<PathBuf>,
 }
 
 impl Drop for Enumerator {
@@ -37,143 +59,507 @@ impl Enumerator {
 
             ::ffi::udev_ref(context.as_ptr());
 
-            Ok(Enumerator { enumerator: ptr })
+            Ok(Enumerator {
+                enumerator: ptr,
+                query: DeviceQuery::new(),
+                subsystems: Vec::new(),
+            })
         }
     }
 
+    /// Returns the filters that have been added to the enumerator.
+    ///
+    /// The filters are returned as a `DeviceQuery`, which can be logged with `{:?}`, inspected,
+    /// or used to create another enumerator with the same filters.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// let context = libudev::Context::new().unwrap();
+    /// let mut enumerator = libudev::Enumerator::new(&context).unwrap();
+    ///
+    /// enumerator.match_subsystems(&["block", "nvme"]).unwrap();
+    /// enumerator.match_tags(&["systemd", "uaccess"]).unwrap();
+    ///
+    /// let query = enumerator.describe();
+    ///
+    /// assert_eq!(query.subsystems(), ["block", "nvme"]);
+    /// assert_eq!(query.tags(), ["systemd", "uaccess"]);
+    /// ```
+    pub fn describe(&self) -> DeviceQuery {
+        self.query.clone()
+    }
+
     /// Adds a filter that matches only initialized devices.
     pub fn match_is_initialized(&mut self) -> ::Result<()> {
         ::util::errno_to_result(unsafe {
             ::ffi::udev_enumerate_add_match_is_initialized(self.enumerator)
-        })
+        })?;
+
+        self.query.initialized = true;
+
+        Ok(())
+    }
+
+    /// Adds a filter that matches only devices that were initialized by udev at least `duration`
+    /// ago.
+    ///
+    /// This is useful to skip devices that udev might still be working on. Devices without an
+    /// initialization time don't match.
+    pub fn match_initialized_for(&mut self, duration: Duration) -> ::Result<()> {
+        self.query.initialized_for = Some(duration);
+
+        Ok(())
+    }
+
+    /// Adds a filter that matches only devices that were initialized by udev at most `duration`
+    /// ago.
+    ///
+    /// Devices without an initialization time don't match.
+    pub fn match_initialized_within(&mut self, duration: Duration) -> ::Result<()> {
+        self.query.initialized_within = Some(duration);
+
+        Ok(())
     }
 
     /// Adds a filter that matches only devices that belong to the given kernel subsystem.
+    ///
+    /// If more than one subsystem is added, devices that belong to any of them match.
     pub fn match_subsystem<T: AsRef<OsStr>>(&mut self, subsystem: T) -> ::Result<()> {
-        let subsystem = try!(::util::os_str_to_cstring(subsystem));
+        let subsystem = subsystem.as_ref();
+        let c_subsystem = ::util::os_str_to_cstring(subsystem)?;
 
         ::util::errno_to_result(unsafe {
-            ::ffi::udev_enumerate_add_match_subsystem(self.enumerator, subsystem.as_ptr())
-        })
+            ::ffi::udev_enumerate_add_match_subsystem(self.enumerator, c_subsystem.as_ptr())
+        })?;
+
+        self.query.subsystems.push(subsystem.to_os_string());
+
+        Ok(())
+    }
+
+    /// Adds a filter that matches only devices that belong to any of the given kernel subsystems.
+    pub fn match_subsystems<I: IntoIterator<Item = T>, T: AsRef<OsStr>>(&mut self, subsystems: I) -> ::Result<()> {
+        for subsystem in subsystems {
+            self.match_subsystem(subsystem)?;
+        }
+
+        Ok(())
     }
 
     /// Adds a filter that matches only devices with the given attribute value.
+    ///
+    /// The value may be a shell-style glob pattern. If more than one attribute is added, devices
+    /// must match all of them.
     pub fn match_attribute<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, attribute: T, value: U) -> ::Result<()> {
-        let attribute = try!(::util::os_str_to_cstring(attribute));
-        let value = try!(::util::os_str_to_cstring(value));
+        let attribute = attribute.as_ref();
+        let value = value.as_ref();
+        let c_attribute = ::util::os_str_to_cstring(attribute)?;
+        let c_value = ::util::os_str_to_cstring(value)?;
 
         ::util::errno_to_result(unsafe {
-            ::ffi::udev_enumerate_add_match_sysattr(self.enumerator, attribute.as_ptr(), value.as_ptr())
-        })
+            ::ffi::udev_enumerate_add_match_sysattr(self.enumerator, c_attribute.as_ptr(), c_value.as_ptr())
+        })?;
+
+        self.query.attributes.push((attribute.to_os_string(), value.to_os_string()));
+
+        Ok(())
+    }
+
+    /// Adds a filter that matches only devices with an attribute value that matches a shell-style
+    /// glob pattern, e.g., `match_attribute_glob("idVendor", "04[0-9]3")`.
+    ///
+    /// This is the same as `match_attribute()`, which also accepts glob patterns, but makes the
+    /// intent explicit.
+    pub fn match_attribute_glob<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, attribute: T, pattern: U) -> ::Result<()> {
+        self.match_attribute(attribute, pattern)
     }
 
     /// Adds a filter that matches only devices with the given kernel device name.
+    ///
+    /// The name may be a shell-style glob pattern. If more than one name is added, devices that
+    /// match any of them match.
     pub fn match_sysname<T: AsRef<OsStr>>(&mut self, sysname: T) -> ::Result<()> {
-        let sysname = try!(::util::os_str_to_cstring(sysname));
+        let sysname = sysname.as_ref();
+        let c_sysname = ::util::os_str_to_cstring(sysname)?;
 
         ::util::errno_to_result(unsafe {
-            ::ffi::udev_enumerate_add_match_sysname(self.enumerator, sysname.as_ptr())
-        })
+            ::ffi::udev_enumerate_add_match_sysname(self.enumerator, c_sysname.as_ptr())
+        })?;
+
+        self.query.sysnames.push(sysname.to_os_string());
+
+        Ok(())
     }
 
     /// Adds a filter that matches only devices with the given property value.
+    ///
+    /// The value may be a shell-style glob pattern. If more than one property is added, devices
+    /// that match any of them match.
     pub fn match_property<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, property: T, value: U) -> ::Result<()> {
-        let property = try!(::util::os_str_to_cstring(property));
-        let value = try!(::util::os_str_to_cstring(value));
+        let property = property.as_ref();
+        let value = value.as_ref();
+        let c_property = ::util::os_str_to_cstring(property)?;
+        let c_value = ::util::os_str_to_cstring(value)?;
 
         ::util::errno_to_result(unsafe {
-            ::ffi::udev_enumerate_add_match_property(self.enumerator, property.as_ptr(), value.as_ptr())
-        })
+            ::ffi::udev_enumerate_add_match_property(self.enumerator, c_property.as_ptr(), c_value.as_ptr())
+        })?;
+
+        self.query.properties.push((property.to_os_string(), value.to_os_string()));
+
+        Ok(())
     }
 
     /// Adds a filter that matches only devices with the given tag.
+    ///
+    /// If more than one tag is added, devices must have all of them.
     pub fn match_tag<T: AsRef<OsStr>>(&mut self, tag: T) -> ::Result<()> {
-        let tag = try!(::util::os_str_to_cstring(tag));
+        let tag = tag.as_ref();
+        let c_tag = ::util::os_str_to_cstring(tag)?;
 
         ::util::errno_to_result(unsafe {
-            ::ffi::udev_enumerate_add_match_tag(self.enumerator, tag.as_ptr())
-        })
+            ::ffi::udev_enumerate_add_match_tag(self.enumerator, c_tag.as_ptr())
+        })?;
+
+        self.query.tags.push(tag.to_os_string());
+
+        Ok(())
+    }
+
+    /// Adds a filter that matches only devices with all of the given tags.
+    pub fn match_tags<I: IntoIterator<Item = T>, T: AsRef<OsStr>>(&mut self, tags: I) -> ::Result<()> {
+        for tag in tags {
+            self.match_tag(tag)?;
+        }
+
+        Ok(())
     }
 
     /// Includes the parent device and all devices in the subtree of the parent device.
+    ///
+    /// libudev only supports one parent, so this replaces any parent that was added before.
     pub fn match_parent(&mut self, parent: &Device) -> ::Result<()> {
         ::util::errno_to_result(unsafe {
             ::ffi::udev_enumerate_add_match_parent(self.enumerator, parent.as_ptr())
-        })
+        })?;
+
+        self.query.parent = parent.syspath().map(|syspath| syspath.to_path_buf());
+
+        Ok(())
     }
 
     /// Adds a filter that matches only devices that don't belong to the given kernel subsystem.
     pub fn nomatch_subsystem<T: AsRef<OsStr>>(&mut self, subsystem: T) -> ::Result<()> {
-        let subsystem = try!(::util::os_str_to_cstring(subsystem));
+        let subsystem = subsystem.as_ref();
+        let c_subsystem = ::util::os_str_to_cstring(subsystem)?;
 
         ::util::errno_to_result(unsafe {
-            ::ffi::udev_enumerate_add_nomatch_subsystem(self.enumerator, subsystem.as_ptr())
-        })
+            ::ffi::udev_enumerate_add_nomatch_subsystem(self.enumerator, c_subsystem.as_ptr())
+        })?;
+
+        self.query.nomatch_subsystems.push(subsystem.to_os_string());
+
+        Ok(())
+    }
+
+    /// Adds a filter that matches only devices that don't have the given kernel device name.
+    ///
+    /// The name may be a shell-style glob pattern. libudev doesn't support this filter, so it's
+    /// applied by this library to the devices found by a scan.
+    pub fn nomatch_sysname<T: AsRef<OsStr>>(&mut self, sysname: T) -> ::Result<()> {
+        let sysname = sysname.as_ref();
+
+        // Validate the name the same way as the filters that are passed to libudev.
+        ::util::os_str_to_cstring(sysname)?;
+
+        self.query.nomatch_sysnames.push(sysname.to_os_string());
+
+        Ok(())
     }
 
     /// Adds a filter that matches only devices that don't have the the given attribute value.
     pub fn nomatch_attribute<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, attribute: T, value: U) -> ::Result<()> {
-        let attribute = try!(::util::os_str_to_cstring(attribute));
-        let value = try!(::util::os_str_to_cstring(value));
+        let attribute = attribute.as_ref();
+        let value = value.as_ref();
+        let c_attribute = ::util::os_str_to_cstring(attribute)?;
+        let c_value = ::util::os_str_to_cstring(value)?;
 
         ::util::errno_to_result(unsafe {
-            ::ffi::udev_enumerate_add_nomatch_sysattr(self.enumerator, attribute.as_ptr(), value.as_ptr())
-        })
+            ::ffi::udev_enumerate_add_nomatch_sysattr(self.enumerator, c_attribute.as_ptr(), c_value.as_ptr())
+        })?;
+
+        self.query.nomatch_attributes.push((attribute.to_os_string(), value.to_os_string()));
+
+        Ok(())
     }
 
     /// Includes the device with the given syspath.
     pub fn add_syspath(&mut self, syspath: &Path) -> ::Result<()> {
-        let syspath = try!(::util::os_str_to_cstring(syspath));
+        let c_syspath = ::util::os_str_to_cstring(syspath)?;
 
         ::util::errno_to_result(unsafe {
-            ::ffi::udev_enumerate_add_syspath(self.enumerator, syspath.as_ptr())
-        })
+            ::ffi::udev_enumerate_add_syspath(self.enumerator, c_syspath.as_ptr())
+        })?;
+
+        self.query.syspaths.push(syspath.to_path_buf());
+
+        Ok(())
     }
 
     /// Scans `/sys` for devices matching the attached filters.
     ///
-    /// The devices will be sorted in dependency order.
-    pub fn scan_devices(&mut self) -> ::Result<Devices> {
-        try!(::util::errno_to_result(unsafe {
+    /// The devices will be sorted in dependency order. Devices that can't be read, e.g., because
+    /// they were removed during the scan, are skipped. Use `try_scan_devices()` to find out about
+    /// them.
+    pub fn scan_devices(&mut self) -> ::Result<Devices<'_>> {
+        Ok(Devices {
+            inner: self.try_scan_devices()?,
+        })
+    }
+
+    /// Scans `/sys` for devices matching the attached filters, reporting devices that can't be
+    /// read.
+    ///
+    /// Like `scan_devices()`, but the iterator yields a `ScanError` for each device that was found
+    /// by the scan but couldn't be read. The error includes the device's syspath and the `errno`
+    /// reported by libudev, so that callers can tell a device that has been removed from one
+    /// that they don't have permission to read.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// let context = libudev::Context::new().unwrap();
+    /// let mut enumerator = libudev::Enumerator::new(&context).unwrap();
+    ///
+    /// for result in enumerator.try_scan_devices().unwrap() {
+    ///     match result {
+    ///         Ok(device) => println!("{:?}", device.syspath()),
+    ///         Err(ref err) if err.is_gone() => {},
+    ///         Err(err) => eprintln!("{}", err),
+    ///     }
+    /// }
+    /// ```
+    pub fn try_scan_devices(&mut self) -> ::Result<TryDevices<'_>> {
+        ::util::errno_to_result(unsafe {
             ::ffi::udev_enumerate_scan_devices(self.enumerator)
-        }));
+        })?;
 
         unsafe {
-            Ok(Devices {
-                _enumerator: PhantomData,
+            Ok(TryDevices {
+                enumerator: self,
                 udev: ::ffi::udev_enumerate_get_udev(self.enumerator),
                 entry: ::ffi::udev_enumerate_get_list_entry(self.enumerator),
             })
         }
     }
+
+    /// Scans `/sys` for devices matching the attached filters and returns their syspaths.
+    ///
+    /// Unlike `scan_devices()`, this doesn't read any information about the devices, which makes
+    /// it much faster for large numbers of devices. Use `DeviceRef` to read a device only when it's
+    /// needed.
+    ///
+    /// Excluded sysnames are applied to the syspaths. Filters on initialization time need to read
+    /// the devices, so they are not applied.
+    pub fn scan_syspaths(&mut self) -> ::Result<Syspaths<'_>> {
+        ::util::errno_to_result(unsafe {
+            ::ffi::udev_enumerate_scan_devices(self.enumerator)
+        })?;
+
+        unsafe {
+            Ok(Syspaths {
+                query: &self.query,
+                entry: ::ffi::udev_enumerate_get_list_entry(self.enumerator),
+            })
+        }
+    }
+
+    /// Scans `/sys` for subsystems matching the attached filters.
+    ///
+    /// The iterator yields the syspaths of the buses below `/sys/bus`, such as `/sys/bus/usb`,
+    /// followed by the syspaths of the device classes below `/sys/class`, such as
+    /// `/sys/class/tty`. The last component of each syspath is the name of the subsystem.
+    ///
+    /// The subsystem and sysname filters match the name of the subsystem, so
+    /// `match_subsystem("usb")` finds `/sys/bus/usb` and `nomatch_subsystem("tty")` excludes
+    /// `/sys/class/tty`.
+    ///
+    /// libudev applies the property, attribute, tag, and parent filters to buses as if they were
+    /// devices. Classes have no properties, attributes, tags, or parents, so they're only included
+    /// if there are no such filters. Filters on initialization time don't apply.
+    pub fn scan_subsystems(&mut self) -> ::Result<Subsystems<'_>> {
+        let mut buses = scan_buses(self.enumerator, &self.query)?;
+        let mut classes = scan_classes(&self.query)?;
+
+        buses.append(&mut classes);
+        self.subsystems = buses;
+
+        Ok(Subsystems {
+            syspaths: self.subsystems.iter(),
+        })
+    }
+
+    fn accepts(&self, device: &Device) -> bool {
+        self.query.matches_initialization_time(device)
+    }
 }
 
 
 /// Iterator over devices.
 pub struct Devices<'a> {
-    _enumerator: PhantomData<&'a Enumerator>,
-    udev: *mut ::ffi::udev,
-    entry: *mut ::ffi::udev_list_entry,
+    inner: TryDevices<'a>,
 }
 
 impl<'a> Iterator for Devices<'a> {
     type Item = Device;
 
     fn next(&mut self) -> Option<Device> {
+        self.inner.by_ref().find_map(|result| result.ok())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<'a> Devices<'a> {
+    /// Collects the devices in the given order, removing duplicates.
+    ///
+    /// libudev returns devices in an order that depends on its internals and on the kernel. Sorting
+    /// the devices makes the result deterministic. Devices that compare equal in the given order
+    /// are sorted by syspath, and devices with the same syspath are only returned once.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use libudev::SortOrder;
+    ///
+    /// let context = libudev::Context::new().unwrap();
+    /// let mut enumerator = libudev::Enumerator::new(&context).unwrap();
+    /// enumerator.match_subsystem("tty").unwrap();
+    ///
+    /// // ttyUSB2 is listed before ttyUSB10.
+    /// for device in enumerator.scan_devices().unwrap().sorted(SortOrder::Sysnum) {
+    ///     println!("{:?}", device.sysname());
+    /// }
+    /// ```
+    pub fn sorted(self, order: SortOrder) -> Vec<Device> {
+        let mut devices: Vec<Device> = self.collect();
+
+        devices.sort_by(|a, b| order.compare(a, b).then_with(|| a.syspath().cmp(&b.syspath())));
+        devices.dedup_by(|a, b| a.syspath() == b.syspath());
+
+        devices
+    }
+
+    /// Collects the devices, ordered from the earliest to the most recently initialized.
+    ///
+    /// Devices without an initialization time are placed at the end.
+    pub fn sort_by_initialization(self) -> Vec<Device> {
+        let mut devices: Vec<(Option<Duration>, Device)> = self.map(|d| (d.usec_since_initialized(), d)).collect();
+
+        // A longer time since initialization means that the device was initialized earlier.
+        devices.sort_by(|a, b| match (a.0, b.0) {
+            (Some(a), Some(b)) => b.cmp(&a),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+
+        devices.into_iter().map(|(_, d)| d).collect()
+    }
+}
+
+
+/// Orders in which `Devices::sorted()` can return devices.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum SortOrder {
+    /// Sorts devices by devpath, byte by byte.
+    Devpath,
+
+    /// Sorts devices so that each device is followed by its subtree, i.e., parents come before
+    /// their children. Devpaths are compared component by component.
+    Topology,
+
+    /// Sorts devices by major and minor device number. Devices without a device number come last.
+    Devnum,
+
+    /// Sorts devices by sysname prefix and then numerically by sysnum, so that `ttyUSB2` comes
+    /// before `ttyUSB10`. Within a prefix, devices without a sysnum come first.
+    Sysnum,
+}
+
+impl SortOrder {
+    fn compare(&self, a: &Device, b: &Device) -> Ordering {
+        match *self {
+            SortOrder::Devpath => a.devpath().cmp(&b.devpath()),
+            SortOrder::Topology => a.devpath().map(Path::new).cmp(&b.devpath().map(Path::new)),
+            SortOrder::Devnum => {
+                let devnum = |d: &Device| d.devnum().map(|n| (::libc::major(n), ::libc::minor(n)));
+
+                match (devnum(a), devnum(b)) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+            },
+            SortOrder::Sysnum => {
+                sysname_prefix(a).cmp(&sysname_prefix(b))
+                    .then_with(|| a.sysnum().cmp(&b.sysnum()))
+                    .then_with(|| a.sysname().cmp(&b.sysname()))
+            },
+        }
+    }
+}
+
+/// Returns the sysname without its trailing digits.
+fn sysname_prefix(device: &Device) -> Option<&[u8]> {
+    device.sysname().map(|sysname| {
+        let bytes = sysname.as_bytes();
+        let digits = bytes.iter().rev().take_while(|b| b.is_ascii_digit()).count();
+
+        &bytes[..bytes.len() - digits]
+    })
+}
+
+/// Iterator over devices that reports devices that can't be read.
+pub struct TryDevices<'a> {
+    enumerator: &'a Enumerator,
+    udev: *mut ::ffi::udev,
+    entry: *mut ::ffi::udev_list_entry,
+}
+
+impl<'a> Iterator for TryDevices<'a> {
+    type Item = Result<Device, ScanError>;
+
+    fn next(&mut self) -> Option<Result<Device, ScanError>> {
         while !self.entry.is_null() {
             unsafe {
                 let syspath = ::ffi::udev_list_entry_get_name(self.entry);
 
                 self.entry = ::ffi::udev_list_entry_get_next(self.entry);
 
-                let device = ::ffi::udev_device_new_from_syspath(self.udev, syspath);
-
-                if !device.is_null() {
-                    return Some(::device::from_raw(device));
-                }
-                else {
+                // Excluded devices are skipped before they're read, so that they aren't reported
+                // if they can't be read.
+                if !self.enumerator.query.matches_nomatch_sysnames(::util::ptr_to_path(syspath).and_then(|p| p.file_name())) {
                     continue;
                 }
+
+                match ::device::from_syspath_raw(self.udev, syspath) {
+                    Ok(device) => {
+                        if self.enumerator.accepts(&device) {
+                            return Some(Ok(device));
+                        }
+                    },
+                    Err(error) => {
+                        return Some(Err(ScanError {
+                            syspath: ::util::ptr_to_path(syspath).map(|p| p.to_path_buf()).unwrap_or_default(),
+                            error,
+                        }));
+                    },
+                }
             };
         }
 
@@ -184,3 +570,164 @@ impl<'a> Iterator for Devices<'a> {
         (0, None)
     }
 }
+
+
+/// An error for a device that was found by a scan but couldn't be read.
+#[derive(Debug)]
+pub struct ScanError {
+    syspath: PathBuf,
+    error: ::Error,
+}
+
+impl ScanError {
+    /// Returns the syspath of the device.
+    pub fn syspath(&self) -> &Path {
+        &self.syspath
+    }
+
+    /// Returns the error that occurred while reading the device.
+    pub fn error(&self) -> &::Error {
+        &self.error
+    }
+
+    /// Returns the OS error code (`errno`) reported by libudev.
+    pub fn raw_os_error(&self) -> ::libc::c_int {
+        self.error.raw_os_error()
+    }
+
+    /// Returns `true` if the device no longer exists.
+    pub fn is_gone(&self) -> bool {
+        self.raw_os_error() == ::libc::ENODEV || self.raw_os_error() == ::libc::ENOENT
+    }
+
+    /// Returns `true` if the device couldn't be read for lack of permission.
+    pub fn is_permission_denied(&self) -> bool {
+        self.raw_os_error() == ::libc::EACCES || self.raw_os_error() == ::libc::EPERM
+    }
+
+    /// Converts the error into the underlying libudev error.
+    pub fn into_error(self) -> ::Error {
+        self.error
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.syspath.display(), self.error)
+    }
+}
+
+impl error::Error for ScanError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Iterator over the syspaths of devices.
+pub struct Syspaths<'a> {
+    query: &'a DeviceQuery,
+    entry: *mut ::ffi::udev_list_entry,
+}
+
+impl<'a> Iterator for Syspaths<'a> {
+    type Item = &'a Path;
+
+    fn next(&mut self) -> Option<&'a Path> {
+        while !self.entry.is_null() {
+            unsafe {
+                let syspath = ::util::ptr_to_path(::ffi::udev_list_entry_get_name(self.entry));
+
+                self.entry = ::ffi::udev_list_entry_get_next(self.entry);
+
+                if let Some(syspath) = syspath {
+                    if self.query.matches_nomatch_sysnames(syspath.file_name()) {
+                        return Some(syspath);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+
+/// Iterator over the syspaths of subsystems.
+pub struct Subsystems<'a> {
+    syspaths: slice::Iter<'a, PathBuf>,
+}
+
+impl<'a> Iterator for Subsystems<'a> {
+    type Item = &'a Path;
+
+    fn next(&mut self) -> Option<&'a Path> {
+        self.syspaths.next().map(|syspath| syspath.as_path())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.syspaths.size_hint()
+    }
+}
+
+/// Returns the syspaths of the buses below `/sys/bus` that match `query`, in the order returned
+/// by libudev.
+///
+/// libudev matches its subsystem filters against the subsystem of each entry, which is
+/// `subsystem` for buses, so the buses are scanned by a separate enumerator that only has the
+/// query's other filters. The query's subsystem filters are then applied to the bus names.
+fn scan_buses(enumerator: *mut ::ffi::udev_enumerate, query: &DeviceQuery) -> ::Result<Vec<PathBuf>> {
+    let context = unsafe { ::context::from_raw(::ffi::udev_enumerate_get_udev(enumerator)) };
+
+    let mut bus_query = query.clone();
+    bus_query.subsystems = vec![OsStr::new("subsystem").to_os_string()];
+    bus_query.nomatch_subsystems.clear();
+    bus_query.syspaths.clear();
+    bus_query.initialized_for = None;
+    bus_query.initialized_within = None;
+
+    let buses = bus_query.enumerator(&context)?;
+
+    ::util::errno_to_result(unsafe {
+        ::ffi::udev_enumerate_scan_subsystems(buses.enumerator)
+    })?;
+
+    let mut syspaths = Vec::new();
+    let mut entry = unsafe { ::ffi::udev_enumerate_get_list_entry(buses.enumerator) };
+
+    while !entry.is_null() {
+        unsafe {
+            if let Some(syspath) = ::util::ptr_to_path(::ffi::udev_list_entry_get_name(entry)) {
+                if syspath.file_name().is_some_and(|name| query.matches_subsystem_name(name)) {
+                    syspaths.push(syspath.to_path_buf());
+                }
+            }
+
+            entry = ::ffi::udev_list_entry_get_next(entry);
+        }
+    }
+
+    Ok(syspaths)
+}
+
+/// Returns the syspaths of the device classes below `/sys/class` that match `query`, sorted by
+/// name.
+fn scan_classes(query: &DeviceQuery) -> ::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir("/sys/class") {
+        Ok(entries) => entries,
+        Err(err) => return Err(::error::from_errno(-err.raw_os_error().unwrap_or(::libc::EIO))),
+    };
+
+    let mut classes: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| query.matches_class(&entry.file_name()))
+        .map(|entry| entry.path())
+        .collect();
+
+    classes.sort();
+
+    Ok(classes)
+}
@@ -41,6 +41,11 @@ impl Error {
         }
     }
 
+    /// Returns the OS error code (`errno`) for this error.
+    pub fn raw_os_error(&self) -> c_int {
+        self.errno
+    }
+
     /// Returns a description of the error.
     pub fn description(&self) -> &str {
         self.strerror()
@@ -74,3 +79,7 @@ impl From<Error> for io::Error {
 pub fn from_errno(errno: c_int) -> Error {
     Error { errno: -errno }
 }
+
+pub fn last_os_error() -> Error {
+    Error { errno: io::Error::last_os_error().raw_os_error().unwrap_or(::libc::EIO) }
+}
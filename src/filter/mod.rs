@@ -0,0 +1,468 @@
+//! Client-side device filters.
+//!
+//! libudev can only match devices by exact values and glob patterns. The filters in this module
+//! are evaluated by this library, so they can express conditions that libudev can't, such as
+//! numeric comparisons, regular expressions, set membership, missing values, and arbitrary
+//! combinations of conditions on a device and its parents. Regular expressions require the
+//! optional `regex` feature.
+//!
+//! Filters are built from `Field`s, which name a piece of information about a device, and combined
+//! with `and()`, `or()`, and `not()`. A filter can be evaluated against anything that implements
+//! `DeviceInfo`.
+//!
+//! Filters can also be parsed from the match keys of udev rules with `parse()`, which allows them
+//! to be configured with strings like `SUBSYSTEM=="tty", ATTRS{idVendor}=="0403"`.
+//!
+//! ## Example
+//!
+//! This example finds non-empty block devices on a USB or ATA bus, as well as any block device
+//! without a driver:
+//!
+//! ```no_run
+//! use libudev::filter::{Field, Filter};
+//!
+//! let filter = Field::subsystem().eq("block").and(
+//!     Field::attribute("size").gt("0").and(Field::property("ID_BUS").one_of(&["usb", "ata"]))
+//!         .or(Field::driver().exists().not()));
+//!
+//! let context = libudev::Context::new().unwrap();
+//! let mut enumerator = libudev::Enumerator::new(&context).unwrap();
+//!
+//! for device in enumerator.scan_devices().unwrap().filter(|device| filter.matches(device)) {
+//!     println!("{:?}", device.syspath());
+//! }
+//! ```
+
+use std::cmp::Ordering;
+use std::str::{self, FromStr};
+
+use std::ffi::{OsStr, OsString};
+use std::os::unix::prelude::*;
+
+#[cfg(feature = "regex")]
+use regex::bytes::Regex;
+
+use ::info::DeviceInfo;
+
+pub use self::syntax::ParseError;
+
+pub(crate) mod syntax;
+
+
+/// A piece of information about a device that a filter can test.
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub enum Field {
+    /// The device's devpath.
+    Devpath,
+
+    /// The device's subsystem.
+    Subsystem,
+
+    /// The device's kernel name.
+    Sysname,
+
+    /// The device's devtype.
+    Devtype,
+
+    /// The name of the device's driver.
+    Driver,
+
+    /// The path to the device's node.
+    Devnode,
+
+    /// The value of a device property.
+    Property(OsString),
+
+    /// The value of a device attribute.
+    Attribute(OsString),
+}
+
+impl Field {
+    /// Returns the `Devpath` field.
+    pub fn devpath() -> Field {
+        Field::Devpath
+    }
+
+    /// Returns the `Subsystem` field.
+    pub fn subsystem() -> Field {
+        Field::Subsystem
+    }
+
+    /// Returns the `Sysname` field.
+    pub fn sysname() -> Field {
+        Field::Sysname
+    }
+
+    /// Returns the `Devtype` field.
+    pub fn devtype() -> Field {
+        Field::Devtype
+    }
+
+    /// Returns the `Driver` field.
+    pub fn driver() -> Field {
+        Field::Driver
+    }
+
+    /// Returns the `Devnode` field.
+    pub fn devnode() -> Field {
+        Field::Devnode
+    }
+
+    /// Returns the field for the given property.
+    pub fn property<T: AsRef<OsStr>>(name: T) -> Field {
+        Field::Property(name.as_ref().to_os_string())
+    }
+
+    /// Returns the field for the given attribute.
+    pub fn attribute<T: AsRef<OsStr>>(name: T) -> Field {
+        Field::Attribute(name.as_ref().to_os_string())
+    }
+
+    /// Reads the field's value from a device.
+    pub fn value<'a, D: DeviceInfo + ?Sized>(&self, device: &'a D) -> Option<&'a OsStr> {
+        match *self {
+            Field::Devpath => device.devpath(),
+            Field::Subsystem => device.subsystem(),
+            Field::Sysname => device.sysname(),
+            Field::Devtype => device.devtype(),
+            Field::Driver => device.driver(),
+            Field::Devnode => device.devnode().map(|path| path.as_os_str()),
+            Field::Property(ref name) => device.property_value(name),
+            Field::Attribute(ref name) => device.attribute_value(name),
+        }
+    }
+
+    /// Returns a filter that matches if the field equals `value`.
+    pub fn eq<T: AsRef<OsStr>>(self, value: T) -> Filter {
+        Filter::Compare(self, Comparison::Equal, value.as_ref().to_os_string())
+    }
+
+    /// Returns a filter that matches if the field exists and doesn't equal `value`.
+    pub fn ne<T: AsRef<OsStr>>(self, value: T) -> Filter {
+        Filter::Compare(self, Comparison::NotEqual, value.as_ref().to_os_string())
+    }
+
+    /// Returns a filter that matches if the field is less than `value`.
+    pub fn lt<T: AsRef<OsStr>>(self, value: T) -> Filter {
+        Filter::Compare(self, Comparison::Less, value.as_ref().to_os_string())
+    }
+
+    /// Returns a filter that matches if the field is less than or equal to `value`.
+    pub fn le<T: AsRef<OsStr>>(self, value: T) -> Filter {
+        Filter::Compare(self, Comparison::LessOrEqual, value.as_ref().to_os_string())
+    }
+
+    /// Returns a filter that matches if the field is greater than `value`.
+    pub fn gt<T: AsRef<OsStr>>(self, value: T) -> Filter {
+        Filter::Compare(self, Comparison::Greater, value.as_ref().to_os_string())
+    }
+
+    /// Returns a filter that matches if the field is greater than or equal to `value`.
+    pub fn ge<T: AsRef<OsStr>>(self, value: T) -> Filter {
+        Filter::Compare(self, Comparison::GreaterOrEqual, value.as_ref().to_os_string())
+    }
+
+    /// Returns a filter that matches if the field matches a shell-style glob pattern.
+    pub fn glob<T: AsRef<OsStr>>(self, pattern: T) -> Filter {
+        Filter::Glob(self, pattern.as_ref().to_os_string())
+    }
+
+    /// Returns a filter that matches if the field matches a regular expression.
+    ///
+    /// The regular expression is not anchored. Use `^` and `$` to match the whole value. This
+    /// requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn regex(self, regex: Regex) -> Filter {
+        Filter::Regex(self, regex)
+    }
+
+    /// Returns a filter that matches if the field equals any of `values`.
+    pub fn one_of<T: AsRef<OsStr>>(self, values: &[T]) -> Filter {
+        Filter::OneOf(self, values.iter().map(|v| v.as_ref().to_os_string()).collect())
+    }
+
+    /// Returns a filter that matches if the device has a value for the field.
+    pub fn exists(self) -> Filter {
+        Filter::Exists(self)
+    }
+}
+
+
+/// Ways of comparing a field to a value.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Comparison {
+    /// The field is equal to the value.
+    Equal,
+
+    /// The field is not equal to the value.
+    NotEqual,
+
+    /// The field is less than the value.
+    Less,
+
+    /// The field is less than or equal to the value.
+    LessOrEqual,
+
+    /// The field is greater than the value.
+    Greater,
+
+    /// The field is greater than or equal to the value.
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Compares a field's value to an operand.
+    ///
+    /// `Equal` and `NotEqual` compare strings exactly. The ordering comparisons compare numbers if
+    /// both sides parse as numbers and compare strings byte-wise otherwise. Numbers may be decimal
+    /// integers, hexadecimal integers prefixed with `0x`, or decimal fractions.
+    pub fn test(&self, value: &OsStr, operand: &OsStr) -> bool {
+        let ordering = || compare_numbers(value, operand).unwrap_or_else(|| value.as_bytes().cmp(operand.as_bytes()));
+
+        match *self {
+            Comparison::Equal => value == operand,
+            Comparison::NotEqual => value != operand,
+            Comparison::Less => ordering() == Ordering::Less,
+            Comparison::LessOrEqual => ordering() != Ordering::Greater,
+            Comparison::Greater => ordering() == Ordering::Greater,
+            Comparison::GreaterOrEqual => ordering() != Ordering::Less,
+        }
+    }
+}
+
+
+/// A condition on a device.
+///
+/// Every condition on a field fails if the device doesn't have a value for the field, so
+/// `Field::driver().ne("usb")` doesn't match devices without a driver. Use `not()` to include
+/// them.
+///
+/// ## Example
+///
+/// ```
+/// use libudev::DeviceSnapshot;
+/// use libudev::filter::Field;
+///
+/// let mut device = DeviceSnapshot::new();
+/// device.set_property("SUBSYSTEM", "block").unwrap();
+/// device.set_property("ID_BUS", "usb").unwrap();
+/// device.set_property("MINOR", "16").unwrap();
+///
+/// assert!(Field::property("ID_BUS").one_of(&["usb", "ata"]).matches(&device));
+/// assert!(Field::property("MINOR").gt("9").matches(&device));
+/// assert!(Field::driver().exists().not().matches(&device));
+/// assert!(!Field::driver().ne("sd").matches(&device));
+/// ```
+#[derive(Debug,Clone)]
+pub enum Filter {
+    /// Matches every device.
+    True,
+
+    /// Compares a field to a value.
+    Compare(Field, Comparison, OsString),
+
+    /// Matches a field against a shell-style glob pattern.
+    Glob(Field, OsString),
+
+    /// Matches a field against a regular expression. This requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    Regex(Field, Regex),
+
+    /// Matches if a field equals any of the values.
+    OneOf(Field, Vec<OsString>),
+
+    /// Matches if the device has a value for a field.
+    Exists(Field),
+
+    /// Matches if the device has a tag.
+    Tag(OsString),
+
+    /// Matches if the device has a tag that matches a shell-style glob pattern.
+    TagGlob(OsString),
+
+    /// Matches if the device's parent matches the filter.
+    Parent(Box<Filter>),
+
+    /// Matches if the device or any of its ancestors matches the filter.
+    Ancestor(Box<Filter>),
+
+    /// Matches if the filter doesn't match.
+    Not(Box<Filter>),
+
+    /// Matches if all of the filters match.
+    All(Vec<Filter>),
+
+    /// Matches if any of the filters match.
+    Any(Vec<Filter>),
+}
+
+impl Filter {
+    /// Returns a filter that matches devices with the given tag.
+    pub fn tag<T: AsRef<OsStr>>(tag: T) -> Filter {
+        Filter::Tag(tag.as_ref().to_os_string())
+    }
+
+    /// Returns a filter that matches devices with a tag that matches a shell-style glob pattern.
+    pub fn tag_glob<T: AsRef<OsStr>>(pattern: T) -> Filter {
+        Filter::TagGlob(pattern.as_ref().to_os_string())
+    }
+
+    /// Returns a filter that matches devices whose parent matches `filter`.
+    pub fn parent(filter: Filter) -> Filter {
+        Filter::Parent(Box::new(filter))
+    }
+
+    /// Returns a filter that matches devices for which the device itself or one of its ancestors
+    /// matches `filter`.
+    ///
+    /// The whole filter must match a single device in the chain. This is how udev evaluates keys
+    /// such as `ATTRS` and `DRIVERS`.
+    pub fn ancestor(filter: Filter) -> Filter {
+        Filter::Ancestor(Box::new(filter))
+    }
+
+    /// Combines two filters so that both must match.
+    pub fn and(self, other: Filter) -> Filter {
+        match self {
+            Filter::All(mut filters) => {
+                filters.push(other);
+                Filter::All(filters)
+            },
+            filter => Filter::All(vec![filter, other]),
+        }
+    }
+
+    /// Combines two filters so that either may match.
+    pub fn or(self, other: Filter) -> Filter {
+        match self {
+            Filter::Any(mut filters) => {
+                filters.push(other);
+                Filter::Any(filters)
+            },
+            filter => Filter::Any(vec![filter, other]),
+        }
+    }
+
+    /// Inverts the filter.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Filter {
+        match self {
+            Filter::Not(filter) => *filter,
+            filter => Filter::Not(Box::new(filter)),
+        }
+    }
+
+    /// Evaluates the filter against a device.
+    pub fn matches<D: DeviceInfo>(&self, device: &D) -> bool {
+        self.evaluate(device)
+    }
+
+    pub(crate) fn evaluate(&self, device: &dyn DeviceInfo) -> bool {
+        match *self {
+            Filter::True => true,
+            Filter::Compare(ref field, comparison, ref operand) => {
+                field.value(device).is_some_and(|value| comparison.test(value, operand))
+            },
+            Filter::Glob(ref field, ref pattern) => {
+                field.value(device).is_some_and(|value| ::util::fnmatch(pattern, value))
+            },
+            #[cfg(feature = "regex")]
+            Filter::Regex(ref field, ref regex) => {
+                field.value(device).is_some_and(|value| regex.is_match(value.as_bytes()))
+            },
+            Filter::OneOf(ref field, ref values) => {
+                field.value(device).is_some_and(|value| values.iter().any(|v| v.as_os_str() == value))
+            },
+            Filter::Exists(ref field) => field.value(device).is_some(),
+            Filter::Tag(ref tag) => device.has_tag(tag),
+            Filter::TagGlob(ref pattern) => device.visit_tags(&mut |tag| ::util::fnmatch(pattern, tag)),
+            Filter::Parent(ref filter) => device.visit_parent(&mut |parent| filter.evaluate(parent)),
+            Filter::Ancestor(ref filter) => any_ancestor(device, &mut |d| filter.evaluate(d)),
+            Filter::Not(ref filter) => !filter.evaluate(device),
+            Filter::All(ref filters) => filters.iter().all(|f| f.evaluate(device)),
+            Filter::Any(ref filters) => filters.iter().any(|f| f.evaluate(device)),
+        }
+    }
+}
+
+/// Parses a filter with `parse()`.
+impl FromStr for Filter {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Filter, ParseError> {
+        parse(s)
+    }
+}
+
+
+/// Parses the match keys of a udev rule into a filter.
+///
+/// The input is a comma-separated list of `KEY=="value"` or `KEY!="value"` pairs, which must all
+/// match. Values are shell-style glob patterns, and `|` separates alternative patterns. An empty
+/// pattern matches a missing value. The supported keys are:
+///
+/// * `ACTION`, which matches the action of a monitor event.
+/// * `DEVPATH`, `KERNEL`, `SUBSYSTEM`, and `DRIVER`, which match the device's devpath, sysname,
+///   subsystem, and driver.
+/// * `ATTR{name}` and `ENV{name}`, which match an attribute and a property.
+/// * `TAG`, which matches a tag.
+/// * `KERNELS`, `SUBSYSTEMS`, `DRIVERS`, `ATTRS{name}`, and `TAGS`, which match the device or any
+///   of its ancestors. Like in udev, all of these keys must match the same device.
+///
+/// Assignments and other keys are rejected. An empty string parses to a filter that matches every
+/// device.
+///
+/// ## Example
+///
+/// ```
+/// use libudev::DeviceSnapshot;
+///
+/// let filter = libudev::filter::parse(r#"SUBSYSTEM=="tty", ENV{ID_SERIAL}=="*FT232*|*CP210*", ENV{ID_MODEL}!="Test", TAG=="uacc*""#).unwrap();
+///
+/// let mut device = DeviceSnapshot::new();
+/// device.set_property("SUBSYSTEM", "tty").unwrap();
+/// device.set_property("ID_SERIAL", "FTDI_FT232R_USB_UART_A50285BI").unwrap();
+/// device.set_property("TAGS", ":systemd:uaccess:").unwrap();
+///
+/// assert!(filter.matches(&device));
+///
+/// assert_eq!(libudev::filter::parse(r#"SUBSYSTEM="tty""#).unwrap_err().offset(), 0);
+/// ```
+pub fn parse(s: &str) -> Result<Filter, ParseError> {
+    syntax::compile(&syntax::tokenize(s)?)
+}
+
+
+/// Calls `predicate` on the device and then on each of its ancestors until it returns `true`.
+pub(crate) fn any_ancestor(device: &dyn DeviceInfo, predicate: &mut dyn FnMut(&dyn DeviceInfo) -> bool) -> bool {
+    predicate(device) || device.visit_parent(&mut |parent| any_ancestor(parent, predicate))
+}
+
+fn compare_numbers(a: &OsStr, b: &OsStr) -> Option<Ordering> {
+    if let (Some(a), Some(b)) = (parse_integer(a), parse_integer(b)) {
+        return Some(a.cmp(&b));
+    }
+
+    match (parse_float(a), parse_float(b)) {
+        (Some(a), Some(b)) => a.partial_cmp(&b),
+        _ => None,
+    }
+}
+
+fn parse_integer(s: &OsStr) -> Option<i128> {
+    let s = match str::from_utf8(s.as_bytes()) {
+        Ok(s) => s.trim(),
+        Err(_) => return None,
+    };
+
+    if s.starts_with("0x") || s.starts_with("0X") {
+        i128::from_str_radix(&s[2..], 16).ok()
+    }
+    else {
+        s.parse().ok()
+    }
+}
+
+fn parse_float(s: &OsStr) -> Option<f64> {
+    str::from_utf8(s.as_bytes()).ok().and_then(|s| s.trim().parse().ok())
+}
@@ -0,0 +1,319 @@
+use std::error;
+use std::fmt;
+
+use std::ffi::OsStr;
+
+use super::{Field, Filter};
+
+
+/// An error that occurred while parsing udev rule syntax.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ParseError {
+    offset: usize,
+    reason: Reason,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub(crate) enum Reason {
+    ExpectedKey,
+    ExpectedOperator,
+    ExpectedValue,
+    ExpectedComma,
+    UnterminatedValue,
+    UnterminatedAttribute,
+    UnknownKey,
+    MissingAttribute,
+    UnexpectedAttribute,
+    InvalidOperator,
+}
+
+impl ParseError {
+    pub(crate) fn new(offset: usize, reason: Reason) -> Self {
+        ParseError { offset, reason }
+    }
+
+    /// Returns the byte offset in the input at which the error was found.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn description(&self) -> &'static str {
+        match self.reason {
+            Reason::ExpectedKey => "expected a key",
+            Reason::ExpectedOperator => "expected an operator",
+            Reason::ExpectedValue => "expected a quoted value",
+            Reason::ExpectedComma => "expected a comma",
+            Reason::UnterminatedValue => "unterminated value",
+            Reason::UnterminatedAttribute => "unterminated attribute name",
+            Reason::UnknownKey => "unknown or unsupported key",
+            Reason::MissingAttribute => "key requires an attribute name",
+            Reason::UnexpectedAttribute => "key doesn't take an attribute name",
+            Reason::InvalidOperator => "operator is not allowed for this key",
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.description(), self.offset)
+    }
+}
+
+impl error::Error for ParseError {}
+
+
+/// The operator of a key-value pair in a udev rule.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub(crate) enum Operator {
+    Match,
+    Nomatch,
+    Assign,
+    Add,
+    Remove,
+    AssignFinal,
+}
+
+impl Operator {
+    pub(crate) fn is_match(self) -> bool {
+        self == Operator::Match || self == Operator::Nomatch
+    }
+}
+
+/// One `KEY{attribute}<op>"value"` pair from a udev rule.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub(crate) struct Pair {
+    pub(crate) key: String,
+    pub(crate) attribute: Option<String>,
+    pub(crate) operator: Operator,
+    pub(crate) value: String,
+    pub(crate) offset: usize,
+}
+
+/// Splits a rule into its comma-separated key-value pairs.
+///
+/// Values are double-quoted. Inside a value, `\"` stands for a quote; any other backslash is kept
+/// as is, like udev does.
+pub(crate) fn tokenize(input: &str) -> Result<Vec<Pair>, ParseError> {
+    let bytes = input.as_bytes();
+    let mut pairs = Vec::new();
+    let mut pos = skip_whitespace(bytes, 0);
+
+    while pos < bytes.len() {
+        let offset = pos;
+
+        let key_end = pos + bytes[pos..].iter().take_while(|&&b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_').count();
+
+        if key_end == pos {
+            return Err(ParseError::new(pos, Reason::ExpectedKey));
+        }
+
+        let key = input[pos..key_end].to_owned();
+        pos = key_end;
+
+        let attribute = if bytes.get(pos) == Some(&b'{') {
+            let end = match bytes[pos..].iter().position(|&b| b == b'}') {
+                Some(n) => pos + n,
+                None => return Err(ParseError::new(pos, Reason::UnterminatedAttribute)),
+            };
+
+            let attribute = input[pos + 1..end].to_owned();
+            pos = end + 1;
+            Some(attribute)
+        }
+        else {
+            None
+        };
+
+        pos = skip_whitespace(bytes, pos);
+
+        let (operator, len) = match bytes.get(pos..pos + 2) {
+            Some(b"==") => (Operator::Match, 2),
+            Some(b"!=") => (Operator::Nomatch, 2),
+            Some(b"+=") => (Operator::Add, 2),
+            Some(b"-=") => (Operator::Remove, 2),
+            Some(b":=") => (Operator::AssignFinal, 2),
+            _ if bytes.get(pos) == Some(&b'=') => (Operator::Assign, 1),
+            _ => return Err(ParseError::new(pos, Reason::ExpectedOperator)),
+        };
+
+        pos = skip_whitespace(bytes, pos + len);
+
+        if bytes.get(pos) != Some(&b'"') {
+            return Err(ParseError::new(pos, Reason::ExpectedValue));
+        }
+
+        let mut value = Vec::new();
+        let start = pos;
+        pos += 1;
+
+        loop {
+            match bytes.get(pos) {
+                Some(b'"') => break,
+                Some(b'\\') if bytes.get(pos + 1) == Some(&b'"') => {
+                    value.push(b'"');
+                    pos += 2;
+                },
+                Some(&b) => {
+                    value.push(b);
+                    pos += 1;
+                },
+                None => return Err(ParseError::new(start, Reason::UnterminatedValue)),
+            }
+        }
+
+        pos = skip_whitespace(bytes, pos + 1);
+
+        pairs.push(Pair {
+            key,
+            attribute,
+            operator,
+            // The value was copied from valid UTF-8 and only split at ASCII characters.
+            value: String::from_utf8(value).unwrap(),
+            offset,
+        });
+
+        match bytes.get(pos) {
+            Some(b',') => pos = skip_whitespace(bytes, pos + 1),
+            Some(_) => return Err(ParseError::new(pos, Reason::ExpectedComma)),
+            None => {},
+        }
+    }
+
+    Ok(pairs)
+}
+
+fn skip_whitespace(bytes: &[u8], pos: usize) -> usize {
+    pos + bytes[pos..].iter().take_while(|b| b.is_ascii_whitespace()).count()
+}
+
+
+/// Compiles the match keys of a udev rule into a filter.
+pub(crate) fn compile(pairs: &[Pair]) -> Result<Filter, ParseError> {
+    let (mut filters, ancestor_filters) = compile_keys(pairs)?;
+
+    if !ancestor_filters.is_empty() {
+        filters.push(Filter::ancestor(all(ancestor_filters)));
+    }
+
+    Ok(all(filters))
+}
+
+/// Compiles the match keys of a udev rule into a filter for the device and, if the rule has keys
+/// that are matched against the device's parents, a filter that one device in the chain must
+/// match.
+pub(crate) fn compile_split(pairs: &[Pair]) -> Result<(Filter, Option<Filter>), ParseError> {
+    let (filters, ancestor_filters) = compile_keys(pairs)?;
+
+    let ancestor_filter = if ancestor_filters.is_empty() {
+        None
+    }
+    else {
+        Some(all(ancestor_filters))
+    };
+
+    Ok((all(filters), ancestor_filter))
+}
+
+/// Compiles each match key, separating the keys that are matched against the device's parents.
+fn compile_keys(pairs: &[Pair]) -> Result<(Vec<Filter>, Vec<Filter>), ParseError> {
+    let mut filters = Vec::new();
+    let mut ancestor_filters = Vec::new();
+
+    for pair in pairs {
+        if !pair.operator.is_match() {
+            return Err(ParseError::new(pair.offset, Reason::InvalidOperator));
+        }
+
+        let (filter, walks_parents) = compile_match(pair)?;
+
+        if walks_parents {
+            ancestor_filters.push(filter);
+        }
+        else {
+            filters.push(filter);
+        }
+    }
+
+    Ok((filters, ancestor_filters))
+}
+
+/// Compiles one match key. Also returns whether the key is matched against the device's parents.
+fn compile_match(pair: &Pair) -> Result<(Filter, bool), ParseError> {
+    if !is_filter_key(&pair.key) {
+        return Err(ParseError::new(pair.offset, Reason::UnknownKey));
+    }
+
+    let takes_attribute = matches!(pair.key.as_str(), "ATTR" | "ATTRS" | "ENV");
+
+    let attribute = match (takes_attribute, pair.attribute.as_ref()) {
+        (true, Some(attribute)) if !attribute.is_empty() => attribute.as_str(),
+        (true, _) => return Err(ParseError::new(pair.offset, Reason::MissingAttribute)),
+        (false, Some(_)) => return Err(ParseError::new(pair.offset, Reason::UnexpectedAttribute)),
+        (false, None) => "",
+    };
+
+    let field = match pair.key.as_str() {
+        "ACTION" => Field::property("ACTION"),
+        "DEVPATH" => Field::devpath(),
+        "KERNEL" | "KERNELS" => Field::sysname(),
+        "SUBSYSTEM" | "SUBSYSTEMS" => Field::subsystem(),
+        "DRIVER" | "DRIVERS" => Field::driver(),
+        "ATTR" | "ATTRS" => Field::attribute(attribute),
+        "ENV" => Field::property(attribute),
+        _ => {
+            let filter = any(pair.value.split('|').map(Filter::tag_glob).collect());
+            return Ok((negate_if(filter, pair.operator), pair.key == "TAGS"));
+        },
+    };
+
+    let walks_parents = matches!(pair.key.as_str(), "KERNELS" | "SUBSYSTEMS" | "DRIVERS" | "ATTRS");
+    let filter = negate_if(pattern(&field, &pair.value), pair.operator);
+
+    // udev never matches an attribute that can't be read, whatever the operator.
+    let filter = match field {
+        Field::Attribute(_) if pair.operator == Operator::Nomatch => all(vec![field.exists(), filter]),
+        _ => filter,
+    };
+
+    Ok((filter, walks_parents))
+}
+
+/// Checks whether a match key can be compiled into a filter.
+pub(crate) fn is_filter_key(key: &str) -> bool {
+    matches!(key, "ACTION" | "DEVPATH" | "KERNEL" | "KERNELS" | "SUBSYSTEM" | "SUBSYSTEMS" | "DRIVER" | "DRIVERS" | "ATTR" | "ATTRS" | "ENV" | "TAG" | "TAGS")
+}
+
+/// Builds a filter for a udev match pattern: a list of glob patterns separated by `|`. An empty
+/// pattern matches an empty or missing value.
+fn pattern(field: &Field, value: &str) -> Filter {
+    any(value.split('|').map(|pattern| {
+        if pattern.is_empty() {
+            field.clone().exists().not().or(field.clone().eq(""))
+        }
+        else {
+            field.clone().glob(OsStr::new(pattern))
+        }
+    }).collect())
+}
+
+fn negate_if(filter: Filter, operator: Operator) -> Filter {
+    match operator {
+        Operator::Nomatch => filter.not(),
+        _ => filter,
+    }
+}
+
+fn all(mut filters: Vec<Filter>) -> Filter {
+    match filters.len() {
+        0 => Filter::True,
+        1 => filters.pop().unwrap(),
+        _ => Filter::All(filters),
+    }
+}
+
+fn any(mut filters: Vec<Filter>) -> Filter {
+    match filters.len() {
+        1 => filters.pop().unwrap(),
+        _ => Filter::Any(filters),
+    }
+}
@@ -0,0 +1,459 @@
+//! Typed views of HID devices.
+//!
+//! HID devices are devices in the `hid` subsystem, named after their bus, vendor, product, and
+//! an instance number, e.g., `0003:1050:0407.0001`. Their children include a `hidraw*` device in
+//! the `hidraw` subsystem, whose device node gives raw access to the device's reports, and input
+//! devices for the collections that the kernel understands.
+//!
+//! A HID device describes its reports with a report descriptor. The usage page and usage of its
+//! top-level collections identify what kind of device it is: for example, FIDO security keys use
+//! the usage page `0xF1D0`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use libudev::hid::{HidDevice, UsagePage};
+//!
+//! let context = libudev::Context::new().unwrap();
+//!
+//! for hid in HidDevice::devices(&context).unwrap() {
+//!     let usages = hid.top_level_usages().unwrap();
+//!
+//!     if usages.iter().any(|usage| usage.usage_page() == UsagePage::Fido) {
+//!         let hidraw = hid.hidraw_node().unwrap();
+//!         println!("{:?}: {:?}", hid.name(), hidraw.as_ref().and_then(|node| node.devnode()));
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+
+use std::ffi::OsStr;
+use std::ops::Deref;
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::{Enumerator, SortOrder};
+
+
+/// A HID device.
+///
+/// A `HidDevice` wraps a device in the `hid` subsystem. It dereferences to the underlying
+/// `Device`.
+#[derive(Clone)]
+pub struct HidDevice {
+    device: Device,
+}
+
+impl HidDevice {
+    /// Creates a HID device view of `device`.
+    ///
+    /// Returns `None` if `device` isn't a HID device.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if is_hid(&device) {
+            Some(HidDevice { device })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Finds the HID device that `device` belongs to.
+    ///
+    /// Returns `device` itself if it's a HID device, otherwise its closest HID ancestor. This
+    /// finds the HID device behind a `hidraw` node or an input device.
+    pub fn find(device: &Device) -> Option<Self> {
+        if is_hid(device) {
+            return Some(HidDevice { device: device.clone() });
+        }
+
+        device.parent_with_subsystem("hid").map(|device| HidDevice { device })
+    }
+
+    /// Returns the HID devices in the system, ordered by sysname.
+    pub fn devices(context: &Context) -> ::Result<Vec<HidDevice>> {
+        let mut enumerator = Enumerator::new(context)?;
+
+        enumerator.match_subsystem("hid")?;
+
+        let devices = enumerator.scan_devices()?.sorted(SortOrder::Sysnum);
+
+        Ok(devices.into_iter().filter_map(HidDevice::from_device).collect())
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the underlying device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Returns the `hidraw` device of the HID device.
+    pub fn hidraw_node(&self) -> ::Result<Option<Device>> {
+        let nodes = ::device::children(&self.device, "hidraw")?;
+
+        Ok(nodes.into_iter().next())
+    }
+
+    /// Returns the bus, vendor ID, and product ID of the device (`HID_ID`).
+    pub fn id(&self) -> Option<HidId> {
+        ::util::value_to_str(self.device.property_value("HID_ID")).and_then(HidId::parse)
+    }
+
+    /// Returns the name of the device (`HID_NAME`).
+    pub fn name(&self) -> Option<&str> {
+        self.property("HID_NAME")
+    }
+
+    /// Returns the physical location of the device (`HID_PHYS`).
+    pub fn phys(&self) -> Option<&str> {
+        self.property("HID_PHYS")
+    }
+
+    /// Returns the unique identifier of the device (`HID_UNIQ`), such as a serial number or a
+    /// Bluetooth address.
+    pub fn uniq(&self) -> Option<&str> {
+        self.property("HID_UNIQ")
+    }
+
+    /// Reads the report descriptor of the device from the `report_descriptor` attribute.
+    ///
+    /// The report descriptor is binary, so it's read from sysfs directly rather than through
+    /// libudev, which treats attribute values as strings.
+    pub fn report_descriptor(&self) -> io::Result<Vec<u8>> {
+        match self.device.syspath() {
+            Some(syspath) => fs::read(syspath.join("report_descriptor")),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "device has no syspath")),
+        }
+    }
+
+    /// Reads the report descriptor and returns the usages of its top-level collections.
+    pub fn top_level_usages(&self) -> io::Result<Vec<Usage>> {
+        let descriptor = self.report_descriptor()?;
+
+        Ok(top_level_usages(&descriptor))
+    }
+
+    fn property(&self, property: &str) -> Option<&str> {
+        ::util::value_to_str(self.device.property_value(property)).filter(|value| !value.is_empty())
+    }
+}
+
+impl Deref for HidDevice {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for HidDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HidDevice")
+            .field("syspath", &self.device.syspath())
+            .field("id", &self.id())
+            .field("name", &self.name())
+            .finish()
+    }
+}
+
+
+/// The bus, vendor ID, and product ID of a HID device.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct HidId {
+    bus: u16,
+    vendor_id: u16,
+    product_id: u16,
+}
+
+impl HidId {
+    /// Parses the value of the `HID_ID` property, e.g., `0003:00001050:00000407`.
+    fn parse(value: &str) -> Option<HidId> {
+        let mut parts = value.split(':').map(|part| ::util::parse_hex(Some(OsStr::new(part))));
+
+        let id = HidId {
+            bus: parts.next()??,
+            vendor_id: parts.next()??,
+            product_id: parts.next()??,
+        };
+
+        if parts.next().is_some() {
+            None
+        }
+        else {
+            Some(id)
+        }
+    }
+
+    /// Returns the bus type, one of the kernel's `BUS_*` constants.
+    pub fn bus(&self) -> u16 {
+        self.bus
+    }
+
+    /// Returns the kind of bus the device is connected to.
+    pub fn bus_type(&self) -> BusType {
+        match self.bus {
+            0x03 => BusType::Usb,
+            0x05 => BusType::Bluetooth,
+            0x06 => BusType::Virtual,
+            0x18 => BusType::I2c,
+            0x1c => BusType::Spi,
+            bus => BusType::Other(bus),
+        }
+    }
+
+    /// Returns the vendor ID.
+    pub fn vendor_id(&self) -> u16 {
+        self.vendor_id
+    }
+
+    /// Returns the product ID.
+    pub fn product_id(&self) -> u16 {
+        self.product_id
+    }
+}
+
+/// The bus that a HID device is connected to.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum BusType {
+    /// A USB device (`BUS_USB`).
+    Usb,
+
+    /// A Bluetooth device (`BUS_BLUETOOTH`).
+    Bluetooth,
+
+    /// An I2C device (`BUS_I2C`).
+    I2c,
+
+    /// An SPI device (`BUS_SPI`).
+    Spi,
+
+    /// A device created by software, e.g., with `uhid`.
+    Virtual,
+
+    /// A bus that this crate doesn't know about.
+    Other(u16),
+}
+
+
+/// The usage of a collection in a report descriptor: a usage page and a usage ID within it.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Usage {
+    page: u16,
+    id: u16,
+}
+
+impl Usage {
+    /// Creates a usage from its page and ID.
+    pub fn new(page: u16, id: u16) -> Self {
+        Usage { page, id }
+    }
+
+    /// Returns the raw usage page.
+    pub fn page(&self) -> u16 {
+        self.page
+    }
+
+    /// Returns the usage ID within the usage page.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Returns the usage page.
+    pub fn usage_page(&self) -> UsagePage {
+        UsagePage::from_u16(self.page)
+    }
+}
+
+/// A HID usage page.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum UsagePage {
+    /// Pointers, mice, joysticks, gamepads, keyboards, and system controls (0x01).
+    GenericDesktop,
+
+    /// Simulation controls (0x02).
+    Simulation,
+
+    /// Game controls (0x05).
+    Game,
+
+    /// Generic device controls, such as battery strength (0x06).
+    GenericDevice,
+
+    /// Keyboard keys (0x07).
+    Keyboard,
+
+    /// LEDs (0x08).
+    Led,
+
+    /// Buttons (0x09).
+    Button,
+
+    /// Telephony devices (0x0B).
+    Telephony,
+
+    /// Consumer controls, such as media keys (0x0C).
+    Consumer,
+
+    /// Digitizers, such as touchscreens and pens (0x0D).
+    Digitizer,
+
+    /// Haptics (0x0E).
+    Haptics,
+
+    /// Sensors (0x20).
+    Sensor,
+
+    /// Power devices, such as UPSes (0x84).
+    Power,
+
+    /// Battery systems (0x85).
+    BatterySystem,
+
+    /// FIDO authenticators, such as security keys (0xF1D0).
+    Fido,
+
+    /// A vendor-defined page (0xFF00 to 0xFFFF).
+    VendorDefined(u16),
+
+    /// A page that this crate doesn't know about.
+    Other(u16),
+}
+
+impl UsagePage {
+    fn from_u16(page: u16) -> UsagePage {
+        match page {
+            0x01 => UsagePage::GenericDesktop,
+            0x02 => UsagePage::Simulation,
+            0x05 => UsagePage::Game,
+            0x06 => UsagePage::GenericDevice,
+            0x07 => UsagePage::Keyboard,
+            0x08 => UsagePage::Led,
+            0x09 => UsagePage::Button,
+            0x0b => UsagePage::Telephony,
+            0x0c => UsagePage::Consumer,
+            0x0d => UsagePage::Digitizer,
+            0x0e => UsagePage::Haptics,
+            0x20 => UsagePage::Sensor,
+            0x84 => UsagePage::Power,
+            0x85 => UsagePage::BatterySystem,
+            0xf1d0 => UsagePage::Fido,
+            0xff00..=0xffff => UsagePage::VendorDefined(page),
+            _ => UsagePage::Other(page),
+        }
+    }
+}
+
+
+/// Decodes the usages of the top-level collections of a report descriptor.
+///
+/// Only the items needed to track usages and collections are interpreted; reports themselves
+/// aren't decoded. Decoding stops at the first truncated item.
+///
+/// ```
+/// use libudev::hid::{self, Usage, UsagePage};
+///
+/// // The start of a FIDO U2F report descriptor.
+/// let descriptor = [
+///     0x06, 0xd0, 0xf1, // Usage Page (FIDO Alliance)
+///     0x09, 0x01,       // Usage (U2F Authenticator Device)
+///     0xa1, 0x01,       // Collection (Application)
+///     0x09, 0x20,       //   Usage (Input Report Data)
+///     0x15, 0x00,       //   Logical Minimum (0)
+///     0x26, 0xff, 0x00, //   Logical Maximum (255)
+///     0x75, 0x08,       //   Report Size (8)
+///     0x95, 0x40,       //   Report Count (64)
+///     0x81, 0x02,       //   Input (Data, Variable, Absolute)
+///     0xc0,             // End Collection
+/// ];
+///
+/// let usages = hid::top_level_usages(&descriptor);
+///
+/// assert_eq!(usages, vec![Usage::new(0xf1d0, 0x01)]);
+/// assert_eq!(usages[0].usage_page(), UsagePage::Fido);
+/// ```
+pub fn top_level_usages(descriptor: &[u8]) -> Vec<Usage> {
+    let mut usages = Vec::new();
+    let mut usage_page = 0;
+    let mut page_stack = Vec::new();
+    let mut local_usage = None;
+    let mut depth = 0usize;
+    let mut pos = 0;
+
+    while pos < descriptor.len() {
+        let prefix = descriptor[pos];
+
+        // Long items carry their size in the next byte and don't affect usages.
+        if prefix == 0xfe {
+            match descriptor.get(pos + 1) {
+                Some(&size) => pos += 3 + size as usize,
+                None => break,
+            }
+
+            continue;
+        }
+
+        let size = match prefix & 0x03 {
+            3 => 4,
+            size => size as usize,
+        };
+
+        let data = match descriptor.get(pos + 1..pos + 1 + size) {
+            Some(data) => data,
+            None => break,
+        };
+
+        let value = data.iter().rev().fold(0u32, |value, &b| value << 8 | b as u32);
+        pos += 1 + size;
+
+        match prefix & 0xfc {
+            // Main item: Collection
+            0xa0 => {
+                if depth == 0 {
+                    if let Some(usage) = local_usage {
+                        usages.push(usage_from(usage, usage_page));
+                    }
+                }
+
+                depth += 1;
+                local_usage = None;
+            },
+            // Main item: End Collection
+            0xc0 => {
+                depth = depth.saturating_sub(1);
+                local_usage = None;
+            },
+            // Other main items: Input, Output, Feature
+            0x80 | 0x90 | 0xb0 => local_usage = None,
+            // Global item: Usage Page
+            0x04 => usage_page = value as u16,
+            // Global item: Push
+            0xa4 => page_stack.push(usage_page),
+            // Global item: Pop
+            0xb4 => usage_page = page_stack.pop().unwrap_or(usage_page),
+            // Local item: Usage. Only the first usage before a main item is used.
+            0x08 if local_usage.is_none() => local_usage = Some((value, size)),
+            _ => {},
+        }
+    }
+
+    usages
+}
+
+/// Builds a usage from a local Usage item. A 4-byte usage includes its own usage page.
+fn usage_from(usage: (u32, usize), usage_page: u16) -> Usage {
+    match usage {
+        (value, 4) => Usage::new((value >> 16) as u16, value as u16),
+        (value, _) => Usage::new(usage_page, value as u16),
+    }
+}
+
+
+fn is_hid(device: &Device) -> bool {
+    device.subsystem() == Some(OsStr::new("hid"))
+}
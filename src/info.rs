@@ -0,0 +1,194 @@
+use std::ffi::OsStr;
+use std::path::Path;
+
+use ::device::Device;
+use ::monitor::wire::Message;
+use ::snapshot::DeviceSnapshot;
+
+
+/// Read access to the information that describes a device.
+///
+/// This trait is implemented by `Device`, which reads the information from sysfs and the udev
+/// database, by `DeviceSnapshot`, which holds a copy of it, and by monitor messages. Code that
+/// only inspects devices, like filters, can be written once for all of them.
+pub trait DeviceInfo {
+    /// Returns the kernel devpath value of the device.
+    fn devpath(&self) -> Option<&OsStr>;
+
+    /// Returns the subsystem name of the device.
+    fn subsystem(&self) -> Option<&OsStr>;
+
+    /// Returns the kernel device name for the device.
+    fn sysname(&self) -> Option<&OsStr>;
+
+    /// Returns the devtype name of the device.
+    fn devtype(&self) -> Option<&OsStr>;
+
+    /// Returns the name of the kernel driver attached to the device.
+    fn driver(&self) -> Option<&OsStr>;
+
+    /// Returns the path to the device node belonging to the device.
+    fn devnode(&self) -> Option<&Path>;
+
+    /// Retrieves the value of a device property.
+    fn property_value(&self, property: &OsStr) -> Option<&OsStr>;
+
+    /// Retrieves the value of a device attribute.
+    fn attribute_value(&self, attribute: &OsStr) -> Option<&OsStr>;
+
+    /// Checks whether the device has the given tag.
+    fn has_tag(&self, tag: &OsStr) -> bool;
+
+    /// Calls `visitor` with each of the device's tags until it returns `true`. Returns `true` if
+    /// it did.
+    fn visit_tags(&self, visitor: &mut dyn FnMut(&OsStr) -> bool) -> bool;
+
+    /// Calls `visitor` with the device's parent and returns its result, or returns `false` if the
+    /// device doesn't have a parent.
+    fn visit_parent(&self, visitor: &mut dyn FnMut(&dyn DeviceInfo) -> bool) -> bool;
+}
+
+impl DeviceInfo for Device {
+    fn devpath(&self) -> Option<&OsStr> {
+        Device::devpath(self)
+    }
+
+    fn subsystem(&self) -> Option<&OsStr> {
+        Device::subsystem(self)
+    }
+
+    fn sysname(&self) -> Option<&OsStr> {
+        Device::sysname(self)
+    }
+
+    fn devtype(&self) -> Option<&OsStr> {
+        Device::devtype(self)
+    }
+
+    fn driver(&self) -> Option<&OsStr> {
+        Device::driver(self)
+    }
+
+    fn devnode(&self) -> Option<&Path> {
+        Device::devnode(self)
+    }
+
+    fn property_value(&self, property: &OsStr) -> Option<&OsStr> {
+        Device::property_value(self, property)
+    }
+
+    fn attribute_value(&self, attribute: &OsStr) -> Option<&OsStr> {
+        Device::attribute_value(self, attribute)
+    }
+
+    fn has_tag(&self, tag: &OsStr) -> bool {
+        Device::has_tag(self, tag)
+    }
+
+    fn visit_tags(&self, visitor: &mut dyn FnMut(&OsStr) -> bool) -> bool {
+        self.tags().any(visitor)
+    }
+
+    fn visit_parent(&self, visitor: &mut dyn FnMut(&dyn DeviceInfo) -> bool) -> bool {
+        match self.parent() {
+            Some(parent) => visitor(&parent),
+            None => false,
+        }
+    }
+}
+
+impl DeviceInfo for DeviceSnapshot {
+    fn devpath(&self) -> Option<&OsStr> {
+        DeviceSnapshot::devpath(self)
+    }
+
+    fn subsystem(&self) -> Option<&OsStr> {
+        DeviceSnapshot::subsystem(self)
+    }
+
+    fn sysname(&self) -> Option<&OsStr> {
+        DeviceSnapshot::sysname(self)
+    }
+
+    fn devtype(&self) -> Option<&OsStr> {
+        DeviceSnapshot::devtype(self)
+    }
+
+    fn driver(&self) -> Option<&OsStr> {
+        DeviceSnapshot::driver(self)
+    }
+
+    fn devnode(&self) -> Option<&Path> {
+        DeviceSnapshot::devnode(self)
+    }
+
+    fn property_value(&self, property: &OsStr) -> Option<&OsStr> {
+        DeviceSnapshot::property_value(self, property)
+    }
+
+    fn attribute_value(&self, attribute: &OsStr) -> Option<&OsStr> {
+        DeviceSnapshot::attribute_value(self, attribute)
+    }
+
+    fn has_tag(&self, tag: &OsStr) -> bool {
+        DeviceSnapshot::has_tag(self, tag)
+    }
+
+    fn visit_tags(&self, visitor: &mut dyn FnMut(&OsStr) -> bool) -> bool {
+        self.tags().any(visitor)
+    }
+
+    fn visit_parent(&self, visitor: &mut dyn FnMut(&dyn DeviceInfo) -> bool) -> bool {
+        match self.parent() {
+            Some(parent) => visitor(parent),
+            None => false,
+        }
+    }
+}
+
+/// Messages only carry properties. They have no attributes and no parent.
+impl DeviceInfo for Message {
+    fn devpath(&self) -> Option<&OsStr> {
+        Message::devpath(self)
+    }
+
+    fn subsystem(&self) -> Option<&OsStr> {
+        Message::subsystem(self)
+    }
+
+    fn sysname(&self) -> Option<&OsStr> {
+        self.devpath().and_then(|devpath| Path::new(devpath).file_name())
+    }
+
+    fn devtype(&self) -> Option<&OsStr> {
+        Message::devtype(self)
+    }
+
+    fn driver(&self) -> Option<&OsStr> {
+        self.property_value("DRIVER")
+    }
+
+    fn devnode(&self) -> Option<&Path> {
+        self.property_value("DEVNAME").map(Path::new)
+    }
+
+    fn property_value(&self, property: &OsStr) -> Option<&OsStr> {
+        Message::property_value(self, property)
+    }
+
+    fn attribute_value(&self, _attribute: &OsStr) -> Option<&OsStr> {
+        None
+    }
+
+    fn has_tag(&self, tag: &OsStr) -> bool {
+        self.tags().any(|t| t == tag)
+    }
+
+    fn visit_tags(&self, visitor: &mut dyn FnMut(&OsStr) -> bool) -> bool {
+        self.tags().any(visitor)
+    }
+
+    fn visit_parent(&self, _visitor: &mut dyn FnMut(&dyn DeviceInfo) -> bool) -> bool {
+        false
+    }
+}
@@ -0,0 +1,496 @@
+//! Typed views of input devices.
+//!
+//! The kernel represents each input device with an `input*` device in the `input` subsystem,
+//! which describes the device and its capabilities. Its children are the device nodes that
+//! programs read events from: an `event*` node for the evdev interface and, for some devices, a
+//! legacy `mouse*` or `js*` node. udev's `input_id` builtin classifies input devices and sets
+//! `ID_INPUT_*` properties on all of them.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use libudev::input::{InputDevice, InputType};
+//!
+//! let context = libudev::Context::new().unwrap();
+//!
+//! for input in InputDevice::devices(&context).unwrap() {
+//!     if input.types().contains(InputType::Touchpad) {
+//!         let event = input.event_node().unwrap();
+//!         println!("{:?}: {:?}", input.name(), event.and_then(|event| event.devnode().map(|node| node.to_owned())));
+//!     }
+//! }
+//! ```
+
+use std::error;
+use std::fmt;
+use std::mem;
+
+use std::ffi::OsStr;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use libc::c_ulong;
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::{Enumerator, SortOrder};
+
+
+/// An input device.
+///
+/// An `InputDevice` wraps the `input*` device of an input device. It can be created from the
+/// `input*` device or from one of its device nodes, such as an `event*` node. It dereferences to
+/// the `input*` device.
+#[derive(Clone)]
+pub struct InputDevice {
+    device: Device,
+}
+
+impl InputDevice {
+    /// Creates an input device view of `device`.
+    ///
+    /// `device` can be an `input*` device or one of its device nodes, in which case the view
+    /// wraps its parent. Returns `None` if `device` isn't in the `input` subsystem.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if !is_input(&device) {
+            return None;
+        }
+
+        if device.devnode().is_none() {
+            return Some(InputDevice { device });
+        }
+
+        device.parent().filter(is_input).map(|device| InputDevice { device })
+    }
+
+    /// Returns the input devices in the system, ordered by sysname.
+    pub fn devices(context: &Context) -> ::Result<Vec<InputDevice>> {
+        let mut enumerator = Enumerator::new(context)?;
+
+        enumerator.match_subsystem("input")?;
+        enumerator.match_sysname("input*")?;
+
+        let devices = enumerator.scan_devices()?.sorted(SortOrder::Sysnum);
+
+        Ok(devices.into_iter().filter_map(InputDevice::from_device).collect())
+    }
+
+    /// Returns the `input*` device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the `input*` device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Returns the device nodes of the input device, ordered by sysname.
+    pub fn nodes(&self) -> ::Result<Vec<Device>> {
+        ::device::children(&self.device, "input")
+    }
+
+    /// Returns the `event*` node of the input device.
+    pub fn event_node(&self) -> ::Result<Option<Device>> {
+        let nodes = self.nodes()?;
+
+        Ok(nodes.into_iter().find(|node| node.sysname().and_then(|sysname| sysname.to_str()).is_some_and(|sysname| sysname.starts_with("event"))))
+    }
+
+    /// Returns the name of the device as reported by its driver (`name`).
+    pub fn name(&self) -> Option<&str> {
+        ::util::value_to_str(self.device.attribute_value("name"))
+    }
+
+    /// Returns the physical location of the device in the system (`phys`), e.g.,
+    /// `usb-0000:00:14.0-2/input0`.
+    pub fn phys(&self) -> Option<&str> {
+        ::util::value_to_str(self.device.attribute_value("phys")).filter(|phys| !phys.is_empty())
+    }
+
+    /// Returns the unique identifier of the device (`uniq`), such as a serial number.
+    pub fn uniq(&self) -> Option<&str> {
+        ::util::value_to_str(self.device.attribute_value("uniq")).filter(|uniq| !uniq.is_empty())
+    }
+
+    /// Returns the bus type (`id/bustype`), one of the kernel's `BUS_*` constants.
+    pub fn bustype(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("id/bustype"))
+    }
+
+    /// Returns the vendor ID (`id/vendor`).
+    pub fn vendor_id(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("id/vendor"))
+    }
+
+    /// Returns the product ID (`id/product`).
+    pub fn product_id(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("id/product"))
+    }
+
+    /// Returns the version (`id/version`).
+    pub fn version(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("id/version"))
+    }
+
+    /// Returns the kinds of input device that udev has classified the device as.
+    pub fn types(&self) -> InputTypes {
+        InputType::ALL.iter()
+            .filter(|input_type| ::util::parse_flag(self.device.property_value(input_type.property())) == Some(true))
+            .collect()
+    }
+
+    /// Returns the event types that the device supports (`capabilities/ev`), such as `EV_KEY`,
+    /// `EV_REL`, or `EV_ABS`.
+    pub fn event_types(&self) -> Option<Bitmap> {
+        self.capability("ev")
+    }
+
+    /// Returns the keys and buttons that the device has (`capabilities/key`).
+    pub fn keys(&self) -> Option<Bitmap> {
+        self.capability("key")
+    }
+
+    /// Returns the absolute axes that the device reports (`capabilities/abs`).
+    pub fn absolute_axes(&self) -> Option<Bitmap> {
+        self.capability("abs")
+    }
+
+    /// Returns the relative axes that the device reports (`capabilities/rel`).
+    pub fn relative_axes(&self) -> Option<Bitmap> {
+        self.capability("rel")
+    }
+
+    /// Returns the switches that the device has (`capabilities/sw`).
+    pub fn switches(&self) -> Option<Bitmap> {
+        self.capability("sw")
+    }
+
+    fn capability(&self, name: &str) -> Option<Bitmap> {
+        ::util::value_to_str(self.device.attribute_value(format!("capabilities/{}", name))).and_then(|value| value.parse().ok())
+    }
+}
+
+impl Deref for InputDevice {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for InputDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InputDevice")
+            .field("syspath", &self.device.syspath())
+            .field("name", &self.name())
+            .field("types", &self.types())
+            .finish()
+    }
+}
+
+
+/// A kind of input device, as classified by udev's `input_id` builtin.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum InputType {
+    /// The device has keys (`ID_INPUT_KEY`).
+    Key,
+
+    /// A keyboard (`ID_INPUT_KEYBOARD`).
+    Keyboard,
+
+    /// A mouse (`ID_INPUT_MOUSE`).
+    Mouse,
+
+    /// A pointing stick (`ID_INPUT_POINTINGSTICK`).
+    PointingStick,
+
+    /// A touchpad (`ID_INPUT_TOUCHPAD`).
+    Touchpad,
+
+    /// A touchscreen (`ID_INPUT_TOUCHSCREEN`).
+    Touchscreen,
+
+    /// A graphics tablet (`ID_INPUT_TABLET`).
+    Tablet,
+
+    /// The buttons of a graphics tablet (`ID_INPUT_TABLET_PAD`).
+    TabletPad,
+
+    /// A joystick or gamepad (`ID_INPUT_JOYSTICK`).
+    Joystick,
+
+    /// An accelerometer (`ID_INPUT_ACCELEROMETER`).
+    Accelerometer,
+
+    /// A device with switches, such as a lid switch (`ID_INPUT_SWITCH`).
+    Switch,
+
+    /// A trackball (`ID_INPUT_TRACKBALL`).
+    Trackball,
+}
+
+impl InputType {
+    const ALL: [InputType; 12] = [
+        InputType::Key,
+        InputType::Keyboard,
+        InputType::Mouse,
+        InputType::PointingStick,
+        InputType::Touchpad,
+        InputType::Touchscreen,
+        InputType::Tablet,
+        InputType::TabletPad,
+        InputType::Joystick,
+        InputType::Accelerometer,
+        InputType::Switch,
+        InputType::Trackball,
+    ];
+
+    /// Returns the name of the udev property that marks the kind of device.
+    pub fn property(self) -> &'static str {
+        match self {
+            InputType::Key => "ID_INPUT_KEY",
+            InputType::Keyboard => "ID_INPUT_KEYBOARD",
+            InputType::Mouse => "ID_INPUT_MOUSE",
+            InputType::PointingStick => "ID_INPUT_POINTINGSTICK",
+            InputType::Touchpad => "ID_INPUT_TOUCHPAD",
+            InputType::Touchscreen => "ID_INPUT_TOUCHSCREEN",
+            InputType::Tablet => "ID_INPUT_TABLET",
+            InputType::TabletPad => "ID_INPUT_TABLET_PAD",
+            InputType::Joystick => "ID_INPUT_JOYSTICK",
+            InputType::Accelerometer => "ID_INPUT_ACCELEROMETER",
+            InputType::Switch => "ID_INPUT_SWITCH",
+            InputType::Trackball => "ID_INPUT_TRACKBALL",
+        }
+    }
+
+    fn bit(self) -> u16 {
+        1 << (self as u16)
+    }
+}
+
+
+/// A set of kinds of input device.
+///
+/// A device can be of several kinds at once. For example, a keyboard with a touchpad is both a
+/// `Keyboard` and a `Touchpad`, and every keyboard is also a `Key` device.
+///
+/// ```
+/// use libudev::input::{InputType, InputTypes};
+///
+/// let types: InputTypes = vec![InputType::Key, InputType::Keyboard].into_iter().collect();
+///
+/// assert!(types.contains(InputType::Keyboard));
+/// assert!(!types.contains(InputType::Mouse));
+/// assert_eq!(types.iter().count(), 2);
+/// ```
+#[derive(Clone,Copy,Default,PartialEq,Eq,Hash)]
+pub struct InputTypes {
+    bits: u16,
+}
+
+impl InputTypes {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        InputTypes::default()
+    }
+
+    /// Checks whether the set contains the given kind of device.
+    pub fn contains(&self, input_type: InputType) -> bool {
+        self.bits & input_type.bit() != 0
+    }
+
+    /// Adds a kind of device to the set.
+    pub fn insert(&mut self, input_type: InputType) {
+        self.bits |= input_type.bit();
+    }
+
+    /// Removes a kind of device from the set.
+    pub fn remove(&mut self, input_type: InputType) {
+        self.bits &= !input_type.bit();
+    }
+
+    /// Checks whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns an iterator over the kinds of device in the set.
+    pub fn iter(&self) -> InputTypesIter {
+        InputTypesIter {
+            types: *self,
+            index: 0,
+        }
+    }
+}
+
+impl fmt::Debug for InputTypes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> ::std::iter::FromIterator<&'a InputType> for InputTypes {
+    fn from_iter<I: IntoIterator<Item = &'a InputType>>(iter: I) -> Self {
+        iter.into_iter().cloned().collect()
+    }
+}
+
+impl ::std::iter::FromIterator<InputType> for InputTypes {
+    fn from_iter<I: IntoIterator<Item = InputType>>(iter: I) -> Self {
+        let mut types = InputTypes::new();
+
+        for input_type in iter {
+            types.insert(input_type);
+        }
+
+        types
+    }
+}
+
+impl IntoIterator for InputTypes {
+    type Item = InputType;
+    type IntoIter = InputTypesIter;
+
+    fn into_iter(self) -> InputTypesIter {
+        self.iter()
+    }
+}
+
+/// Iterator over a set of kinds of input device.
+pub struct InputTypesIter {
+    types: InputTypes,
+    index: usize,
+}
+
+impl Iterator for InputTypesIter {
+    type Item = InputType;
+
+    fn next(&mut self) -> Option<InputType> {
+        while let Some(&input_type) = InputType::ALL.get(self.index) {
+            self.index += 1;
+
+            if self.types.contains(input_type) {
+                return Some(input_type);
+            }
+        }
+
+        None
+    }
+}
+
+
+/// A capability bitmap of an input device.
+///
+/// The kernel prints capability bitmaps as hexadecimal words of the size of a C `long`, separated
+/// by spaces, with the most significant word first. Bit `n` is set if the device supports the
+/// event code `n`, e.g., `KEY_A` (30) in the `key` bitmap.
+///
+/// ```
+/// use libudev::input::Bitmap;
+///
+/// let ev: Bitmap = "17".parse().unwrap();
+///
+/// assert!(ev.contains(1)); // EV_KEY
+/// assert!(ev.contains(4)); // EV_MSC
+/// assert!(!ev.contains(3)); // EV_ABS
+/// assert_eq!(ev.iter().collect::<Vec<_>>(), vec![0, 1, 2, 4]);
+/// ```
+#[derive(Clone,Default,PartialEq,Eq,Hash)]
+pub struct Bitmap {
+    blocks: Vec<u64>,
+}
+
+impl Bitmap {
+    /// Checks whether the given bit is set.
+    pub fn contains(&self, bit: usize) -> bool {
+        self.blocks.get(bit / 64).is_some_and(|block| block & (1 << (bit % 64)) != 0)
+    }
+
+    /// Returns the number of bits that are set.
+    pub fn len(&self) -> usize {
+        self.blocks.iter().map(|block| block.count_ones() as usize).sum()
+    }
+
+    /// Checks whether no bits are set.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|&block| block == 0)
+    }
+
+    /// Returns an iterator over the bits that are set, in ascending order.
+    pub fn iter(&self) -> Bits<'_> {
+        Bits {
+            bitmap: self,
+            bit: 0,
+        }
+    }
+}
+
+impl fmt::Debug for Bitmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromStr for Bitmap {
+    type Err = ParseBitmapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let word_bits = mem::size_of::<c_ulong>() * 8;
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let mut blocks = vec![0; (words.len() * word_bits).div_ceil(64)];
+
+        for (i, word) in words.iter().rev().enumerate() {
+            let value = u64::from_str_radix(word, 16).map_err(|_| ParseBitmapError)?;
+            let bit = i * word_bits;
+
+            if word_bits < 64 && value >> word_bits != 0 {
+                return Err(ParseBitmapError);
+            }
+
+            blocks[bit / 64] |= value << (bit % 64);
+        }
+
+        Ok(Bitmap { blocks })
+    }
+}
+
+/// Iterator over the bits that are set in a capability bitmap.
+pub struct Bits<'a> {
+    bitmap: &'a Bitmap,
+    bit: usize,
+}
+
+impl<'a> Iterator for Bits<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.bit < self.bitmap.blocks.len() * 64 {
+            let bit = self.bit;
+            self.bit += 1;
+
+            if self.bitmap.contains(bit) {
+                return Some(bit);
+            }
+        }
+
+        None
+    }
+}
+
+/// An error returned when a string isn't a valid capability bitmap.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct ParseBitmapError;
+
+impl fmt::Display for ParseBitmapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid capability bitmap")
+    }
+}
+
+impl error::Error for ParseBitmapError {}
+
+
+fn is_input(device: &Device) -> bool {
+    device.subsystem() == Some(OsStr::new("input"))
+}
@@ -1,11 +1,17 @@
 extern crate libudev_sys as ffi;
 extern crate libc;
+#[cfg(feature = "regex")]
+extern crate regex;
 
 pub use context::Context;
-pub use device::{Device, Properties, Property, Attributes, Attribute};
-pub use enumerator::{Enumerator, Devices};
+pub use device::{Device, DeviceRef, Properties, Property, Attributes, Attribute, Tags, Devlinks};
+pub use enumerator::{Enumerator, Devices, SortOrder, TryDevices, ScanError, Subsystems, Syspaths};
 pub use error::{Result, Error, ErrorKind};
+pub use info::DeviceInfo;
 pub use monitor::{Monitor, MonitorSocket, EventType, Event};
+pub use query::DeviceQuery;
+pub use snapshot::{DeviceSnapshot, SnapshotProperties};
+pub use trigger::{Action, Trigger, Triggered, Uuid};
 
 macro_rules! try_alloc {
     ($exp:expr) => {{
@@ -23,7 +29,25 @@ mod context;
 mod device;
 mod enumerator;
 mod error;
-mod monitor;
+mod info;
+mod query;
+mod snapshot;
+mod trigger;
+
+pub mod block;
+pub mod drm;
+pub mod filter;
+pub mod hid;
+pub mod input;
+pub mod monitor;
+pub mod net;
+pub mod pci;
+pub mod power_supply;
+pub mod rules;
+pub mod serial;
+pub mod sound;
+pub mod tree;
+pub mod usb;
 
 mod handle;
 mod util;
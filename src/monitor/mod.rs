@@ -9,6 +9,9 @@ use ::context::Context;
 use ::device::Device;
 use ::handle::Handle;
 
+pub mod netlink;
+pub mod wire;
+
 
 /// Monitors for device events.
 ///
@@ -46,7 +49,7 @@ impl Monitor {
 
     /// Adds a filter that matches events for devices with the given subsystem.
     pub fn match_subsystem<T: AsRef<OsStr>>(&mut self, subsystem: T) -> ::Result<()> {
-        let subsystem = try!(::util::os_str_to_cstring(subsystem));
+        let subsystem = ::util::os_str_to_cstring(subsystem)?;
 
         ::util::errno_to_result(unsafe {
             ::ffi::udev_monitor_filter_add_match_subsystem_devtype(self.monitor, subsystem.as_ptr(), ptr::null())
@@ -55,8 +58,8 @@ impl Monitor {
 
     /// Adds a filter that matches events for devices with the given subsystem and device type.
     pub fn match_subsystem_devtype<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, subsystem: T, devtype: U) -> ::Result<()> {
-        let subsystem = try!(::util::os_str_to_cstring(subsystem));
-        let devtype = try!(::util::os_str_to_cstring(devtype));
+        let subsystem = ::util::os_str_to_cstring(subsystem)?;
+        let devtype = ::util::os_str_to_cstring(devtype)?;
 
         ::util::errno_to_result(unsafe {
             ::ffi::udev_monitor_filter_add_match_subsystem_devtype(self.monitor, subsystem.as_ptr(), devtype.as_ptr())
@@ -65,7 +68,7 @@ impl Monitor {
 
     /// Adds a filter that matches events for devices with the given tag.
     pub fn match_tag<T: AsRef<OsStr>>(&mut self, tag: T) -> ::Result<()> {
-        let tag = try!(::util::os_str_to_cstring(tag));
+        let tag = ::util::os_str_to_cstring(tag)?;
 
         ::util::errno_to_result(unsafe {
             ::ffi::udev_monitor_filter_add_match_tag(self.monitor, tag.as_ptr())
@@ -83,9 +86,9 @@ impl Monitor {
     ///
     /// This method consumes the `Monitor`.
     pub fn listen(self) -> ::Result<MonitorSocket> {
-        try!(::util::errno_to_result(unsafe {
+        ::util::errno_to_result(unsafe {
             ::ffi::udev_monitor_enable_receiving(self.monitor)
-        }));
+        })?;
 
         Ok(MonitorSocket { inner: self })
     }
@@ -150,6 +153,22 @@ pub enum EventType {
     Unknown,
 }
 
+impl EventType {
+    fn from_action(action: Option<&OsStr>) -> EventType {
+        let value = match action {
+            Some(s) => s.to_str(),
+            None => None,
+        };
+
+        match value {
+            Some("add") => EventType::Add,
+            Some("change") => EventType::Change,
+            Some("remove") => EventType::Remove,
+            _ => EventType::Unknown
+        }
+    }
+}
+
 impl Default for EventType {
     fn default() -> EventType {
         EventType::Unknown
@@ -185,17 +204,7 @@ impl Deref for Event {
 impl Event {
     /// Returns the `EventType` corresponding to this event.
     pub fn event_type(&self) -> EventType {
-        let value = match self.device.property_value("ACTION") {
-            Some(s) => s.to_str(),
-            None => None,
-        };
-
-        match value {
-            Some("add") => EventType::Add,
-            Some("change") => EventType::Change,
-            Some("remove") => EventType::Remove,
-            _ => EventType::Unknown
-        }
+        EventType::from_action(self.device.property_value("ACTION"))
     }
 
     /// Returns the event's sequence number.
@@ -0,0 +1,669 @@
+//! A device monitor that reads the netlink socket directly, without going through libudev.
+//!
+//! The `Monitor` in this module has the same interface as `libudev::Monitor`, but opens its own
+//! `NETLINK_KOBJECT_UEVENT` socket and decodes messages with the `wire` module. It can listen to
+//! either the udev multicast group, which carries events after `udevd` has processed them, or the
+//! kernel multicast group, which carries raw uevents as soon as the kernel emits them.
+//!
+//! Like libudev, the monitor only accepts messages whose sender is authenticated as `root` with
+//! `SCM_CREDENTIALS`. Filters are compiled into a socket filter, so that the kernel drops
+//! non-matching udev messages before they are queued on the socket. Because the kernel doesn't
+//! include filter hashes in raw uevents, filters are also applied to every message that is
+//! received.
+//!
+//! Unlike the other types in this library, the types in this module don't take a `Context`. They
+//! never call into libudev, so they don't need one, and because they don't share a libudev
+//! context, they can be sent to other threads.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use libudev::monitor::netlink::{Group, Monitor};
+//!
+//! let mut monitor = Monitor::new(Group::Udev).unwrap();
+//! monitor.match_subsystem("usb").unwrap();
+//!
+//! let mut socket = monitor.listen().unwrap();
+//!
+//! while let Some(event) = socket.receive_event() {
+//!     println!("{} {:?}", event.event_type(), event.devpath());
+//! }
+//! ```
+//!
+//! ## Testing
+//!
+//! A `MonitorSender` delivers synthetic events in the libudev format, so that code which consumes
+//! events can be tested without `udevd`. `MonitorSender::pair()` connects a sender to a monitor
+//! through a socket pair, which doesn't require any privileges:
+//!
+//! ```
+//! use libudev::DeviceSnapshot;
+//! use libudev::monitor::netlink::MonitorSender;
+//!
+//! let (mut sender, mut monitor) = MonitorSender::pair().unwrap();
+//! monitor.match_subsystem("tty").unwrap();
+//!
+//! let mut socket = monitor.listen().unwrap();
+//!
+//! let mut device = DeviceSnapshot::new();
+//! device.set_property("DEVPATH", "/devices/virtual/tty/tty0").unwrap();
+//! device.set_property("SUBSYSTEM", "tty").unwrap();
+//!
+//! sender.send("add", &device).unwrap();
+//!
+//! let event = socket.receive_event().unwrap();
+//! assert_eq!(event.event_type(), libudev::EventType::Add);
+//! assert_eq!(event.devpath().unwrap(), "/devices/virtual/tty/tty0");
+//! ```
+
+use std::io;
+use std::mem;
+
+use std::ffi::{OsStr, OsString};
+use std::ops::Deref;
+use std::os::unix::prelude::*;
+
+use libc::{c_int, c_void, sock_filter, sock_fprog, socklen_t};
+
+use ::snapshot::DeviceSnapshot;
+
+use super::EventType;
+use super::wire::{self, Format, Message};
+
+const BUFFER_SIZE: usize = 8192;
+
+const PASS: u32 = 0xffffffff;
+const DROP: u32 = 0;
+
+
+/// The netlink multicast groups that device events are sent to.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Group {
+    /// Raw uevents sent by the kernel.
+    Kernel,
+
+    /// Events sent by `udevd` after processing the kernel's uevents.
+    Udev,
+
+    /// A multicast group that isn't used by the kernel or `udevd`, numbered from 3 to 32.
+    ///
+    /// Private groups carry messages in the libudev format. They are useful for delivering
+    /// synthetic events with a `MonitorSender` to a daemon under test without disturbing other
+    /// listeners on the system.
+    Private(u32),
+}
+
+impl Group {
+    fn mask(&self) -> ::Result<u32> {
+        match *self {
+            Group::Kernel => Ok(1),
+            Group::Udev => Ok(2),
+            Group::Private(n) if (3..=32).contains(&n) => Ok(1 << (n - 1)),
+            Group::Private(_) => Err(::error::from_errno(-::libc::EINVAL)),
+        }
+    }
+
+    fn format(&self) -> Format {
+        match *self {
+            Group::Kernel => Format::Kernel,
+            Group::Udev | Group::Private(_) => Format::Udev,
+        }
+    }
+}
+
+/// Where a monitor's socket receives messages from.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+enum Source {
+    Netlink(Group),
+    Pair,
+}
+
+
+/// Monitors for device events by reading a netlink socket.
+///
+/// Filters must be setup before listening for events.
+pub struct Monitor {
+    fd: RawFd,
+    source: Source,
+    subsystems: Vec<(OsString, Option<OsString>)>,
+    tags: Vec<OsString>,
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        unsafe {
+            ::libc::close(self.fd);
+        }
+    }
+}
+
+impl Monitor {
+    /// Creates a new `Monitor` that receives events from the given multicast group.
+    ///
+    /// Unlike `libudev::Monitor::new()`, this doesn't take a `Context`, because the monitor reads
+    /// the socket itself rather than through libudev.
+    pub fn new(group: Group) -> ::Result<Self> {
+        group.mask()?;
+
+        let fd = unsafe {
+            ::libc::socket(::libc::AF_NETLINK, ::libc::SOCK_RAW | ::libc::SOCK_CLOEXEC | ::libc::SOCK_NONBLOCK, ::libc::NETLINK_KOBJECT_UEVENT)
+        };
+
+        if fd < 0 {
+            return Err(::error::last_os_error());
+        }
+
+        Monitor::from_fd(fd, Source::Netlink(group))
+    }
+
+    fn from_fd(fd: RawFd, source: Source) -> ::Result<Self> {
+        let monitor = Monitor {
+            fd,
+            source,
+            subsystems: Vec::new(),
+            tags: Vec::new(),
+        };
+
+        setsockopt(fd, ::libc::SOL_SOCKET, ::libc::SO_PASSCRED, &(1 as c_int))?;
+
+        Ok(monitor)
+    }
+
+    /// Returns the multicast group that the monitor receives events from, or `None` if the
+    /// monitor was created by `MonitorSender::pair()`.
+    pub fn group(&self) -> Option<Group> {
+        match self.source {
+            Source::Netlink(group) => Some(group),
+            Source::Pair => None,
+        }
+    }
+
+    /// Adds a filter that matches events for devices with the given subsystem.
+    pub fn match_subsystem<T: AsRef<OsStr>>(&mut self, subsystem: T) -> ::Result<()> {
+        ::util::os_str_to_cstring(&subsystem)?;
+
+        self.subsystems.push((subsystem.as_ref().to_os_string(), None));
+
+        Ok(())
+    }
+
+    /// Adds a filter that matches events for devices with the given subsystem and device type.
+    pub fn match_subsystem_devtype<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, subsystem: T, devtype: U) -> ::Result<()> {
+        ::util::os_str_to_cstring(&subsystem)?;
+        ::util::os_str_to_cstring(&devtype)?;
+
+        self.subsystems.push((subsystem.as_ref().to_os_string(), Some(devtype.as_ref().to_os_string())));
+
+        Ok(())
+    }
+
+    /// Adds a filter that matches events for devices with the given tag.
+    pub fn match_tag<T: AsRef<OsStr>>(&mut self, tag: T) -> ::Result<()> {
+        ::util::os_str_to_cstring(&tag)?;
+
+        self.tags.push(tag.as_ref().to_os_string());
+
+        Ok(())
+    }
+
+    /// Removes all filters currently set on the monitor.
+    pub fn clear_filters(&mut self) -> ::Result<()> {
+        self.subsystems.clear();
+        self.tags.clear();
+
+        Ok(())
+    }
+
+    /// Listens for events matching the current filters.
+    ///
+    /// This method attaches the socket filter and binds the socket to the monitor's multicast
+    /// group. It consumes the `Monitor`.
+    pub fn listen(self) -> ::Result<MonitorSocket> {
+        self.attach_filter()?;
+
+        if let Source::Netlink(group) = self.source {
+            let address = netlink_address(group)?;
+
+            let result = unsafe {
+                ::libc::bind(self.fd, &address as *const _ as *const ::libc::sockaddr, mem::size_of_val(&address) as socklen_t)
+            };
+
+            if result < 0 {
+                return Err(::error::last_os_error());
+            }
+        }
+
+        Ok(MonitorSocket { inner: self })
+    }
+
+    fn attach_filter(&self) -> ::Result<()> {
+        if self.subsystems.is_empty() && self.tags.is_empty() {
+            return Ok(());
+        }
+
+        let mut program = filter_program(&self.subsystems, &self.tags)?;
+
+        let fprog = sock_fprog {
+            len: program.len() as u16,
+            filter: program.as_mut_ptr(),
+        };
+
+        setsockopt(self.fd, ::libc::SOL_SOCKET, ::libc::SO_ATTACH_FILTER, &fprog)
+    }
+
+    fn matches(&self, message: &Message) -> bool {
+        if !self.subsystems.is_empty() {
+            let subsystem = message.subsystem();
+            let devtype = message.devtype();
+
+            let matched = self.subsystems.iter().any(|(s, d)| {
+                Some(s.as_os_str()) == subsystem && (d.is_none() || d.as_ref().map(|d| d.as_os_str()) == devtype)
+            });
+
+            if !matched {
+                return false;
+            }
+        }
+
+        if !self.tags.is_empty() {
+            let matched = message.tags().any(|tag| self.tags.iter().any(|t| t.as_os_str() == tag));
+
+            if !matched {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+
+/// An active netlink monitor that can receive events.
+///
+/// The socket is nonblocking. A variant of `poll()` should be used on the file descriptor
+/// returned by the `AsRawFd` trait to wait for new events.
+pub struct MonitorSocket {
+    inner: Monitor,
+}
+
+/// Provides raw access to the monitor's socket.
+impl AsRawFd for MonitorSocket {
+    /// Returns the file descriptor of the monitor's socket.
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.fd
+    }
+}
+
+impl MonitorSocket {
+    /// Receives the next available event from the monitor.
+    ///
+    /// Messages that aren't authenticated, can't be parsed, or don't match the monitor's filters
+    /// are discarded. This method does not block. If no events are available, it returns `None`
+    /// immediately.
+    pub fn receive_event(&mut self) -> Option<Event> {
+        let mut buffer = [0u8; BUFFER_SIZE];
+
+        loop {
+            let len = match self.receive(&mut buffer) {
+                Ok(Some(len)) => len,
+                Ok(None) => continue,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => return None,
+            };
+
+            let message = match Message::parse(&buffer[..len]) {
+                Ok(message) => message,
+                Err(_) => continue,
+            };
+
+            let expected = match self.inner.source {
+                Source::Netlink(group) => group.format(),
+                Source::Pair => Format::Udev,
+            };
+
+            if message.format() == expected && self.inner.matches(&message) {
+                return Some(Event { message });
+            }
+        }
+    }
+
+    /// Reads one message into `buffer`. Returns `None` if the message was discarded because its
+    /// sender isn't trusted or it didn't fit in the buffer.
+    fn receive(&self, buffer: &mut [u8]) -> io::Result<Option<usize>> {
+        let mut sender: ::libc::sockaddr_nl = unsafe { mem::zeroed() };
+        let mut control = [0u64; 8];
+
+        let mut iov = ::libc::iovec {
+            iov_base: buffer.as_mut_ptr() as *mut c_void,
+            iov_len: buffer.len(),
+        };
+
+        let mut header: ::libc::msghdr = unsafe { mem::zeroed() };
+        header.msg_name = &mut sender as *mut _ as *mut c_void;
+        header.msg_namelen = mem::size_of_val(&sender) as socklen_t;
+        header.msg_iov = &mut iov;
+        header.msg_iovlen = 1;
+        header.msg_control = control.as_mut_ptr() as *mut c_void;
+        header.msg_controllen = mem::size_of_val(&control) as _;
+
+        let len = unsafe { ::libc::recvmsg(self.inner.fd, &mut header, 0) };
+
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        if header.msg_flags & ::libc::MSG_TRUNC != 0 {
+            return Ok(None);
+        }
+
+        let trusted = match self.inner.source {
+            // Unicast messages and kernel-group messages sent from userspace are never trusted.
+            Source::Netlink(group) => {
+                sender.nl_groups != 0 && !(group == Group::Kernel && sender.nl_pid != 0) && match sender_credentials(&header) {
+                    Some(credentials) => credentials.uid == 0,
+                    None => false,
+                }
+            },
+            // The other end of a socket pair is trusted if it's owned by the same user.
+            Source::Pair => {
+                match sender_credentials(&header) {
+                    Some(credentials) => credentials.uid == 0 || credentials.uid == unsafe { ::libc::geteuid() },
+                    None => false,
+                }
+            },
+        };
+
+        if trusted {
+            Ok(Some(len as usize))
+        }
+        else {
+            Ok(None)
+        }
+    }
+}
+
+
+/// An event received by a netlink monitor.
+pub struct Event {
+    message: Message,
+}
+
+/// Provides access to the message that carried the event.
+impl Deref for Event {
+    type Target = Message;
+
+    fn deref(&self) -> &Message {
+        &self.message
+    }
+}
+
+impl Event {
+    /// Returns the `EventType` corresponding to this event.
+    pub fn event_type(&self) -> EventType {
+        EventType::from_action(self.message.action())
+    }
+
+    /// Returns the event's sequence number, or `0` if the message doesn't have one.
+    pub fn sequence_number(&self) -> u64 {
+        self.message.sequence_number().unwrap_or(0)
+    }
+
+    /// Returns the message that carried the event.
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    /// Consumes the event, returning the message that carried it.
+    pub fn into_message(self) -> Message {
+        self.message
+    }
+
+    /// Copies the properties of the event's device into a snapshot.
+    pub fn snapshot(&self) -> DeviceSnapshot {
+        DeviceSnapshot::from_message(&self.message)
+    }
+}
+
+
+/// Sends synthetic device events in the libudev format.
+///
+/// A sender either multicasts to a netlink group or writes to a socket pair that is connected to
+/// a `Monitor`. Sending to a netlink group requires `CAP_NET_ADMIN`, and because monitors only
+/// trust messages sent by `root`, it's only useful for privileged test environments. A socket pair
+/// works for any user.
+pub struct MonitorSender {
+    fd: RawFd,
+    group: Option<Group>,
+    sequence_number: u64,
+}
+
+impl Drop for MonitorSender {
+    fn drop(&mut self) {
+        unsafe {
+            ::libc::close(self.fd);
+        }
+    }
+}
+
+impl MonitorSender {
+    /// Creates a sender that multicasts messages to the given netlink group.
+    ///
+    /// Like `Monitor::new()`, this doesn't take a `Context`, because messages are encoded and sent
+    /// without libudev.
+    pub fn new(group: Group) -> ::Result<Self> {
+        group.mask()?;
+
+        let fd = unsafe {
+            ::libc::socket(::libc::AF_NETLINK, ::libc::SOCK_RAW | ::libc::SOCK_CLOEXEC, ::libc::NETLINK_KOBJECT_UEVENT)
+        };
+
+        if fd < 0 {
+            return Err(::error::last_os_error());
+        }
+
+        Ok(MonitorSender {
+            fd,
+            group: Some(group),
+            sequence_number: 0,
+        })
+    }
+
+    /// Creates a sender and a `Monitor` that are connected to each other by a socket pair.
+    ///
+    /// The monitor receives messages only from the sender. Filters can be added to the monitor
+    /// before it starts listening, just like a monitor created with `Monitor::new()`.
+    pub fn pair() -> ::Result<(MonitorSender, Monitor)> {
+        let mut fds = [0 as c_int; 2];
+
+        let result = unsafe {
+            ::libc::socketpair(::libc::AF_UNIX, ::libc::SOCK_SEQPACKET | ::libc::SOCK_CLOEXEC, 0, fds.as_mut_ptr())
+        };
+
+        if result < 0 {
+            return Err(::error::last_os_error());
+        }
+
+        let sender = MonitorSender {
+            fd: fds[0],
+            group: None,
+            sequence_number: 0,
+        };
+
+        let nonblocking = unsafe {
+            ::libc::fcntl(fds[1], ::libc::F_SETFL, ::libc::fcntl(fds[1], ::libc::F_GETFL) | ::libc::O_NONBLOCK)
+        };
+
+        if nonblocking < 0 {
+            let error = ::error::last_os_error();
+
+            unsafe {
+                ::libc::close(fds[1]);
+            }
+
+            return Err(error);
+        }
+
+        let monitor = Monitor::from_fd(fds[1], Source::Pair)?;
+
+        Ok((sender, monitor))
+    }
+
+    /// Sends an event for a device.
+    ///
+    /// The message carries the snapshot's properties with `ACTION` set to `action` and `SEQNUM`
+    /// set to the sender's next sequence number, starting from 1.
+    pub fn send<T: AsRef<OsStr>>(&mut self, action: T, device: &DeviceSnapshot) -> ::Result<()> {
+        let mut device = device.clone();
+
+        self.sequence_number += 1;
+
+        device.set_property("ACTION", action)?;
+        device.set_property("SEQNUM", self.sequence_number.to_string())?;
+
+        self.send_message(&device.to_message(Format::Udev))
+    }
+
+    /// Sends a message as it is.
+    pub fn send_message(&mut self, message: &Message) -> ::Result<()> {
+        let bytes = message.to_bytes();
+
+        let result = match self.group {
+            Some(group) => {
+                let address = netlink_address(group)?;
+
+                unsafe {
+                    ::libc::sendto(self.fd,
+                                   bytes.as_ptr() as *const c_void,
+                                   bytes.len(),
+                                   0,
+                                   &address as *const _ as *const ::libc::sockaddr,
+                                   mem::size_of_val(&address) as socklen_t)
+                }
+            },
+            None => unsafe {
+                ::libc::send(self.fd, bytes.as_ptr() as *const c_void, bytes.len(), 0)
+            },
+        };
+
+        if result < 0 {
+            Err(::error::last_os_error())
+        }
+        else {
+            Ok(())
+        }
+    }
+}
+
+
+fn netlink_address(group: Group) -> ::Result<::libc::sockaddr_nl> {
+    let mut address: ::libc::sockaddr_nl = unsafe { mem::zeroed() };
+
+    address.nl_family = ::libc::AF_NETLINK as ::libc::sa_family_t;
+    address.nl_groups = group.mask()?;
+
+    Ok(address)
+}
+
+fn setsockopt<T>(fd: RawFd, level: c_int, name: c_int, value: &T) -> ::Result<()> {
+    let result = unsafe {
+        ::libc::setsockopt(fd, level, name, value as *const T as *const c_void, mem::size_of::<T>() as socklen_t)
+    };
+
+    if result < 0 {
+        Err(::error::last_os_error())
+    }
+    else {
+        Ok(())
+    }
+}
+
+fn sender_credentials(header: &::libc::msghdr) -> Option<::libc::ucred> {
+    unsafe {
+        let cmsg = ::libc::CMSG_FIRSTHDR(header);
+
+        if cmsg.is_null() || (*cmsg).cmsg_level != ::libc::SOL_SOCKET || (*cmsg).cmsg_type != ::libc::SCM_CREDENTIALS {
+            return None;
+        }
+
+        Some(::std::ptr::read_unaligned(::libc::CMSG_DATA(cmsg) as *const ::libc::ucred))
+    }
+}
+
+/// Builds a socket filter that drops udev messages that can't match the given filters.
+///
+/// The program follows the one built by libudev: messages without the libudev magic number are
+/// passed, messages with none of the tags are dropped, and messages are then passed only if they
+/// match one of the subsystem/devtype pairs.
+fn filter_program(subsystems: &[(OsString, Option<OsString>)], tags: &[OsString]) -> ::Result<Vec<sock_filter>> {
+    const LOAD: u32 = ::libc::BPF_LD | ::libc::BPF_W | ::libc::BPF_ABS;
+    const AND: u32 = ::libc::BPF_ALU | ::libc::BPF_AND | ::libc::BPF_K;
+    const JEQ: u32 = ::libc::BPF_JMP | ::libc::BPF_JEQ | ::libc::BPF_K;
+    const RET: u32 = ::libc::BPF_RET | ::libc::BPF_K;
+
+    let mut program = Vec::new();
+
+    program.push(bpf_stmt(LOAD, wire::MAGIC_OFFSET as u32));
+    program.push(bpf_jump(JEQ, wire::UDEV_MONITOR_MAGIC, 1, 0));
+    program.push(bpf_stmt(RET, PASS));
+
+    if !tags.is_empty() {
+        for (index, tag) in tags.iter().enumerate() {
+            let bloom = wire::bloom64(tag.as_bytes());
+            let bloom_hi = (bloom >> 32) as u32;
+            let bloom_lo = bloom as u32;
+
+            // Each tag takes six instructions. A match skips the remaining tags and the final drop.
+            let skip = 1 + (tags.len() - index - 1) * 6;
+
+            if skip > u8::MAX as usize {
+                return Err(::error::from_errno(-::libc::E2BIG));
+            }
+
+            program.push(bpf_stmt(LOAD, wire::TAG_BLOOM_HI_OFFSET as u32));
+            program.push(bpf_stmt(AND, bloom_hi));
+            program.push(bpf_jump(JEQ, bloom_hi, 0, 3));
+            program.push(bpf_stmt(LOAD, wire::TAG_BLOOM_LO_OFFSET as u32));
+            program.push(bpf_stmt(AND, bloom_lo));
+            program.push(bpf_jump(JEQ, bloom_lo, skip as u8, 0));
+        }
+
+        program.push(bpf_stmt(RET, DROP));
+    }
+
+    if !subsystems.is_empty() {
+        for (subsystem, devtype) in subsystems {
+            program.push(bpf_stmt(LOAD, wire::SUBSYSTEM_HASH_OFFSET as u32));
+
+            match devtype {
+                None => {
+                    program.push(bpf_jump(JEQ, wire::hash32(subsystem.as_bytes()), 0, 1));
+                },
+                Some(devtype) => {
+                    program.push(bpf_jump(JEQ, wire::hash32(subsystem.as_bytes()), 0, 3));
+                    program.push(bpf_stmt(LOAD, wire::DEVTYPE_HASH_OFFSET as u32));
+                    program.push(bpf_jump(JEQ, wire::hash32(devtype.as_bytes()), 0, 1));
+                },
+            }
+
+            program.push(bpf_stmt(RET, PASS));
+        }
+
+        program.push(bpf_stmt(RET, DROP));
+    }
+
+    program.push(bpf_stmt(RET, PASS));
+
+    if program.len() > u16::MAX as usize {
+        return Err(::error::from_errno(-::libc::E2BIG));
+    }
+
+    Ok(program)
+}
+
+fn bpf_stmt(code: u32, k: u32) -> sock_filter {
+    bpf_jump(code, k, 0, 0)
+}
+
+fn bpf_jump(code: u32, k: u32, jt: u8, jf: u8) -> sock_filter {
+    sock_filter { code: code as u16, jt, jf, k }
+}
@@ -0,0 +1,634 @@
+//! Encoding and decoding of udev monitor messages.
+//!
+//! Device events are broadcast over `NETLINK_KOBJECT_UEVENT` sockets in one of two formats:
+//!
+//! * The kernel sends raw uevents to the kernel multicast group. A raw uevent is a summary string
+//!   of the form `ACTION@DEVPATH`, followed by a list of `KEY=VALUE` properties. Each string is
+//!   terminated by a NUL byte.
+//! * `udevd` rebroadcasts events to the udev multicast group after processing them. These messages
+//!   start with a fixed-size header that begins with the string `libudev` and contains hashes of
+//!   the device's subsystem, devtype, and tags, which allow receivers to filter messages with a
+//!   socket filter. The header is followed by the device's `KEY=VALUE` properties.
+//!
+//! Parsing never panics. Malformed input is reported with a `ParseError` that describes what was
+//! wrong with the message.
+//!
+//! ## Example
+//!
+//! ```
+//! use libudev::monitor::wire::{Format, Message};
+//!
+//! let mut message = Message::new(Format::Udev);
+//! message.push_property("ACTION", "add").unwrap();
+//! message.push_property("DEVPATH", "/devices/virtual/tty/tty0").unwrap();
+//! message.push_property("SUBSYSTEM", "tty").unwrap();
+//!
+//! let bytes = message.to_bytes();
+//! let parsed = Message::parse(&bytes).unwrap();
+//!
+//! assert_eq!(parsed, message);
+//! assert_eq!(parsed.subsystem().unwrap(), "tty");
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+
+use std::ffi::{OsStr, OsString};
+use std::os::unix::prelude::*;
+
+/// The magic number that identifies messages sent by libudev.
+pub const UDEV_MONITOR_MAGIC: u32 = 0xfeedcafe;
+
+/// The size of the header that starts each libudev message.
+pub const HEADER_SIZE: usize = 40;
+
+const PREFIX: &[u8] = b"libudev\0";
+
+pub(crate) const MAGIC_OFFSET: usize = 8;
+const HEADER_SIZE_OFFSET: usize = 12;
+const PROPERTIES_OFFSET_OFFSET: usize = 16;
+const PROPERTIES_LENGTH_OFFSET: usize = 20;
+pub(crate) const SUBSYSTEM_HASH_OFFSET: usize = 24;
+pub(crate) const DEVTYPE_HASH_OFFSET: usize = 28;
+pub(crate) const TAG_BLOOM_HI_OFFSET: usize = 32;
+pub(crate) const TAG_BLOOM_LO_OFFSET: usize = 36;
+
+
+/// Errors that occur while parsing a monitor message.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum ParseError {
+    /// The message is too short to contain a libudev header.
+    Truncated,
+
+    /// The message starts with the libudev prefix, but its magic number is wrong.
+    BadMagic(u32),
+
+    /// The header size recorded in a libudev header is smaller than the header or larger than the
+    /// message.
+    BadHeaderSize(u32),
+
+    /// The properties section of a libudev header lies outside of the message.
+    PropertiesOutOfBounds,
+
+    /// A kernel message does not start with an `ACTION@DEVPATH` summary.
+    MalformedSummary,
+
+    /// A property at the given byte offset is not a NUL-terminated `KEY=VALUE` string.
+    MalformedProperty(usize),
+
+    /// The message is neither a libudev message nor a kernel uevent.
+    UnknownFormat,
+}
+
+impl ParseError {
+    fn message(&self) -> &'static str {
+        match *self {
+            ParseError::Truncated => "message is shorter than the libudev header",
+            ParseError::BadMagic(_) => "libudev header has the wrong magic number",
+            ParseError::BadHeaderSize(_) => "libudev header size is out of range",
+            ParseError::PropertiesOutOfBounds => "libudev properties lie outside of the message",
+            ParseError::MalformedSummary => "kernel uevent does not start with ACTION@DEVPATH",
+            ParseError::MalformedProperty(_) => "property is not a NUL-terminated KEY=VALUE string",
+            ParseError::UnknownFormat => "message is neither a libudev message nor a kernel uevent",
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::BadMagic(magic) => write!(f, "{} (0x{:08x})", self.message(), magic),
+            ParseError::BadHeaderSize(size) => write!(f, "{} ({})", self.message(), size),
+            ParseError::MalformedProperty(offset) => write!(f, "{} (offset {})", self.message(), offset),
+            _ => f.write_str(self.message()),
+        }
+    }
+}
+
+impl StdError for ParseError {
+    fn description(&self) -> &str {
+        self.message()
+    }
+}
+
+impl From<ParseError> for io::Error {
+    fn from(error: ParseError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+
+/// Computes the 32-bit hash that libudev uses to filter subsystems and devtypes.
+///
+/// The hash is MurmurHash2 with a seed of zero.
+pub fn hash32(data: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1e995;
+    const R: u32 = 24;
+
+    let mut h = data.len() as u32;
+
+    for chunk in data.chunks(4) {
+        if chunk.len() < 4 {
+            if chunk.len() >= 3 {
+                h ^= (chunk[2] as u32) << 16;
+            }
+
+            if chunk.len() >= 2 {
+                h ^= (chunk[1] as u32) << 8;
+            }
+
+            h ^= chunk[0] as u32;
+            h = h.wrapping_mul(M);
+            break;
+        }
+
+        let mut k = u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+
+    h
+}
+
+/// Computes the 64-bit bloom filter bits that libudev sets for a tag.
+pub fn bloom64(data: &[u8]) -> u64 {
+    let hash = hash32(data);
+
+    (1 << (hash & 63)) | (1 << ((hash >> 6) & 63)) | (1 << ((hash >> 12) & 63)) | (1 << ((hash >> 18) & 63))
+}
+
+
+/// The header of a libudev monitor message.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct Header {
+    header_size: u32,
+    properties_offset: u32,
+    properties_length: u32,
+    subsystem_hash: u32,
+    devtype_hash: u32,
+    tag_bloom: u64,
+}
+
+impl Header {
+    /// Parses the header at the start of a libudev message.
+    ///
+    /// The header's offsets are validated against the length of `bytes`, so the properties
+    /// section described by a successfully parsed header can be sliced from `bytes` safely.
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(ParseError::Truncated);
+        }
+
+        if &bytes[..PREFIX.len()] != PREFIX {
+            return Err(ParseError::UnknownFormat);
+        }
+
+        let magic = read_be32(bytes, MAGIC_OFFSET);
+
+        if magic != UDEV_MONITOR_MAGIC {
+            return Err(ParseError::BadMagic(magic));
+        }
+
+        let header = Header {
+            header_size: read_ne32(bytes, HEADER_SIZE_OFFSET),
+            properties_offset: read_ne32(bytes, PROPERTIES_OFFSET_OFFSET),
+            properties_length: read_ne32(bytes, PROPERTIES_LENGTH_OFFSET),
+            subsystem_hash: read_be32(bytes, SUBSYSTEM_HASH_OFFSET),
+            devtype_hash: read_be32(bytes, DEVTYPE_HASH_OFFSET),
+            tag_bloom: (read_be32(bytes, TAG_BLOOM_HI_OFFSET) as u64) << 32 | read_be32(bytes, TAG_BLOOM_LO_OFFSET) as u64,
+        };
+
+        if (header.header_size as usize) < HEADER_SIZE || header.header_size as usize > bytes.len() {
+            return Err(ParseError::BadHeaderSize(header.header_size));
+        }
+
+        let start = header.properties_offset as usize;
+
+        match start.checked_add(header.properties_length as usize) {
+            Some(end) if start >= header.header_size as usize && end <= bytes.len() => Ok(header),
+            _ => Err(ParseError::PropertiesOutOfBounds),
+        }
+    }
+
+    /// Returns the size of the header as recorded in the message.
+    pub fn header_size(&self) -> u32 {
+        self.header_size
+    }
+
+    /// Returns the byte offset of the properties section from the start of the message.
+    pub fn properties_offset(&self) -> u32 {
+        self.properties_offset
+    }
+
+    /// Returns the length of the properties section in bytes.
+    pub fn properties_length(&self) -> u32 {
+        self.properties_length
+    }
+
+    /// Returns the hash of the device's subsystem, or `0` if the device has no subsystem.
+    pub fn subsystem_hash(&self) -> u32 {
+        self.subsystem_hash
+    }
+
+    /// Returns the hash of the device's devtype, or `0` if the device has no devtype.
+    pub fn devtype_hash(&self) -> u32 {
+        self.devtype_hash
+    }
+
+    /// Returns the bloom filter of the device's tags.
+    pub fn tag_bloom(&self) -> u64 {
+        self.tag_bloom
+    }
+
+    /// Serializes the header in the layout used by libudev.
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0; HEADER_SIZE];
+
+        bytes[..PREFIX.len()].copy_from_slice(PREFIX);
+        write_be32(&mut bytes, MAGIC_OFFSET, UDEV_MONITOR_MAGIC);
+        write_ne32(&mut bytes, HEADER_SIZE_OFFSET, self.header_size);
+        write_ne32(&mut bytes, PROPERTIES_OFFSET_OFFSET, self.properties_offset);
+        write_ne32(&mut bytes, PROPERTIES_LENGTH_OFFSET, self.properties_length);
+        write_be32(&mut bytes, SUBSYSTEM_HASH_OFFSET, self.subsystem_hash);
+        write_be32(&mut bytes, DEVTYPE_HASH_OFFSET, self.devtype_hash);
+        write_be32(&mut bytes, TAG_BLOOM_HI_OFFSET, (self.tag_bloom >> 32) as u32);
+        write_be32(&mut bytes, TAG_BLOOM_LO_OFFSET, self.tag_bloom as u32);
+
+        bytes
+    }
+}
+
+
+/// The format of a monitor message.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Format {
+    /// A message with a libudev header, as sent by `udevd`.
+    Udev,
+
+    /// A raw uevent, as sent by the kernel.
+    Kernel,
+}
+
+/// A decoded monitor message.
+///
+/// A message is a list of device properties. Properties are kept in the order in which they
+/// appear in the message.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Message {
+    format: Format,
+    properties: Vec<(OsString, OsString)>,
+}
+
+impl Message {
+    /// Creates an empty message with the given format.
+    pub fn new(format: Format) -> Self {
+        Message {
+            format,
+            properties: Vec::new(),
+        }
+    }
+
+    /// Parses a message in either format.
+    ///
+    /// Messages that start with the libudev prefix are parsed as libudev messages. Any other
+    /// message is parsed as a kernel uevent.
+    ///
+    /// ## Example
+    ///
+    /// Malformed messages are rejected with an error that describes the problem:
+    ///
+    /// ```
+    /// use libudev::monitor::wire::{Format, Message, ParseError};
+    ///
+    /// let mut message = Message::new(Format::Udev);
+    /// message.push_property("ACTION", "add").unwrap();
+    /// let valid = message.to_bytes();
+    ///
+    /// // The message ends inside the header.
+    /// assert_eq!(Message::parse(&valid[..20]), Err(ParseError::Truncated));
+    ///
+    /// // The magic number is stored big-endian after the prefix.
+    /// let mut bytes = valid.clone();
+    /// bytes[8..12].copy_from_slice(&0xdeadbeefu32.to_be_bytes());
+    /// assert_eq!(Message::parse(&bytes), Err(ParseError::BadMagic(0xdeadbeef)));
+    ///
+    /// // The header size is smaller than the header.
+    /// let mut bytes = valid.clone();
+    /// bytes[12..16].copy_from_slice(&32u32.to_ne_bytes());
+    /// assert_eq!(Message::parse(&bytes), Err(ParseError::BadHeaderSize(32)));
+    ///
+    /// // The properties start past the end of the message.
+    /// let mut bytes = valid.clone();
+    /// bytes[16..20].copy_from_slice(&1000u32.to_ne_bytes());
+    /// assert_eq!(Message::parse(&bytes), Err(ParseError::PropertiesOutOfBounds));
+    ///
+    /// // The properties are longer than the message.
+    /// let mut bytes = valid.clone();
+    /// bytes[20..24].copy_from_slice(&u32::MAX.to_ne_bytes());
+    /// assert_eq!(Message::parse(&bytes), Err(ParseError::PropertiesOutOfBounds));
+    ///
+    /// // The kernel summary has no `@`.
+    /// assert_eq!(Message::parse(b"add/devices/virtual/tty/tty0\0ACTION=add\0"), Err(ParseError::MalformedSummary));
+    ///
+    /// // A property has no `=`.
+    /// assert_eq!(Message::parse(b"add@/devices/virtual/tty/tty0\0ACTION\0"), Err(ParseError::MalformedProperty(30)));
+    ///
+    /// // The last property isn't terminated by a NUL byte.
+    /// assert_eq!(Message::parse(b"add@/devices/virtual/tty/tty0\0ACTION=add"), Err(ParseError::MalformedProperty(30)));
+    /// ```
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        if bytes.starts_with(PREFIX) {
+            Message::parse_udev(bytes)
+        }
+        else {
+            Message::parse_kernel(bytes)
+        }
+    }
+
+    /// Parses a libudev message.
+    pub fn parse_udev(bytes: &[u8]) -> Result<Self, ParseError> {
+        let header = Header::parse(bytes)?;
+
+        let start = header.properties_offset as usize;
+        let end = start + header.properties_length as usize;
+
+        Ok(Message {
+            format: Format::Udev,
+            properties: parse_properties(&bytes[start..end], start)?,
+        })
+    }
+
+    /// Parses a kernel uevent.
+    ///
+    /// The summary string is validated, but the message's properties are taken from the
+    /// `KEY=VALUE` strings that follow it. The kernel always repeats the action and devpath in the
+    /// `ACTION` and `DEVPATH` properties.
+    pub fn parse_kernel(bytes: &[u8]) -> Result<Self, ParseError> {
+        let summary_len = match bytes.iter().position(|&b| b == 0) {
+            Some(len) => len,
+            None => return Err(ParseError::MalformedSummary),
+        };
+
+        match bytes[..summary_len].iter().position(|&b| b == b'@') {
+            Some(at) if at > 0 && at + 1 < summary_len => (),
+            _ => return Err(ParseError::MalformedSummary),
+        }
+
+        let start = summary_len + 1;
+
+        Ok(Message {
+            format: Format::Kernel,
+            properties: parse_properties(&bytes[start..], start)?,
+        })
+    }
+
+    /// Returns the format of the message.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Changes the format that the message is serialized with.
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+
+    /// Appends a property to the message.
+    ///
+    /// Returns an `InvalidInput` error if the name is empty or contains `=`, or if either string
+    /// contains a NUL byte, since such a property can't be represented in a message.
+    pub fn push_property<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, name: T, value: U) -> ::Result<()> {
+        let name = name.as_ref();
+        let value = value.as_ref();
+
+        validate_property(name, value)?;
+
+        self.properties.push((name.to_os_string(), value.to_os_string()));
+
+        Ok(())
+    }
+
+    /// Sets the value of a property, replacing the first property with the same name or appending
+    /// the property if the message doesn't contain it.
+    pub fn set_property<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, name: T, value: U) -> ::Result<()> {
+        let position = self.properties.iter().position(|(n, _)| n.as_os_str() == name.as_ref());
+
+        match position {
+            Some(index) => {
+                self.push_property(name, value)?;
+
+                let property = self.properties.pop().unwrap();
+                self.properties[index] = property;
+
+                Ok(())
+            },
+            None => self.push_property(name, value),
+        }
+    }
+
+    /// Retrieves the value of a property.
+    pub fn property_value<T: AsRef<OsStr>>(&self, name: T) -> Option<&OsStr> {
+        self.properties.iter()
+            .find(|(n, _)| n.as_os_str() == name.as_ref())
+            .map(|(_, v)| v.as_os_str())
+    }
+
+    /// Returns an iterator over the message's properties as `(name, value)` pairs.
+    pub fn properties(&self) -> Properties<'_> {
+        Properties {
+            inner: self.properties.iter(),
+        }
+    }
+
+    /// Returns the value of the `ACTION` property.
+    pub fn action(&self) -> Option<&OsStr> {
+        self.property_value("ACTION")
+    }
+
+    /// Returns the value of the `DEVPATH` property.
+    pub fn devpath(&self) -> Option<&OsStr> {
+        self.property_value("DEVPATH")
+    }
+
+    /// Returns the value of the `SUBSYSTEM` property.
+    pub fn subsystem(&self) -> Option<&OsStr> {
+        self.property_value("SUBSYSTEM")
+    }
+
+    /// Returns the value of the `DEVTYPE` property.
+    pub fn devtype(&self) -> Option<&OsStr> {
+        self.property_value("DEVTYPE")
+    }
+
+    /// Returns the value of the `SEQNUM` property.
+    pub fn sequence_number(&self) -> Option<u64> {
+        self.property_value("SEQNUM").and_then(|s| s.to_str()).and_then(|s| s.parse().ok())
+    }
+
+    /// Returns an iterator over the tags in the `TAGS` property.
+    pub fn tags(&self) -> Tags<'_> {
+        Tags::new(self.property_value("TAGS"))
+    }
+
+    /// Computes the libudev header for the message.
+    ///
+    /// The header describes a message whose properties immediately follow the header.
+    pub fn header(&self) -> Header {
+        let tag_bloom = self.tags().fold(0, |bloom, tag| bloom | bloom64(tag.as_bytes()));
+
+        Header {
+            header_size: HEADER_SIZE as u32,
+            properties_offset: HEADER_SIZE as u32,
+            properties_length: self.properties_len() as u32,
+            subsystem_hash: self.subsystem().map_or(0, |s| hash32(s.as_bytes())),
+            devtype_hash: self.devtype().map_or(0, |s| hash32(s.as_bytes())),
+            tag_bloom,
+        }
+    }
+
+    /// Serializes the message in its format.
+    ///
+    /// Kernel messages are prefixed with an `ACTION@DEVPATH` summary built from the `ACTION` and
+    /// `DEVPATH` properties.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.properties_len());
+
+        match self.format {
+            Format::Udev => {
+                bytes.extend_from_slice(&self.header().to_bytes());
+            },
+            Format::Kernel => {
+                bytes.extend_from_slice(self.action().map_or(&[][..], |s| s.as_bytes()));
+                bytes.push(b'@');
+                bytes.extend_from_slice(self.devpath().map_or(&[][..], |s| s.as_bytes()));
+                bytes.push(0);
+            },
+        }
+
+        for (name, value) in &self.properties {
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.push(b'=');
+            bytes.extend_from_slice(value.as_bytes());
+            bytes.push(0);
+        }
+
+        bytes
+    }
+
+    fn properties_len(&self) -> usize {
+        self.properties.iter().fold(0, |len, (name, value)| len + name.len() + value.len() + 2)
+    }
+}
+
+
+/// Iterator over a message's properties.
+pub struct Properties<'a> {
+    inner: ::std::slice::Iter<'a, (OsString, OsString)>,
+}
+
+impl<'a> Iterator for Properties<'a> {
+    type Item = (&'a OsStr, &'a OsStr);
+
+    fn next(&mut self) -> Option<(&'a OsStr, &'a OsStr)> {
+        self.inner.next().map(|(name, value)| (name.as_os_str(), value.as_os_str()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Iterator over the tags in a `TAGS` property.
+pub struct Tags<'a> {
+    inner: ::std::slice::Split<'a, u8, fn(&u8) -> bool>,
+}
+
+impl<'a> Tags<'a> {
+    /// Splits a colon-separated `TAGS` value.
+    pub(crate) fn new(tags: Option<&'a OsStr>) -> Self {
+        Tags {
+            inner: tags.map_or(&[][..], |tags| tags.as_bytes()).split(|&b| b == b':'),
+        }
+    }
+}
+
+impl<'a> Iterator for Tags<'a> {
+    type Item = &'a OsStr;
+
+    fn next(&mut self) -> Option<&'a OsStr> {
+        for tag in self.inner.by_ref() {
+            if !tag.is_empty() {
+                return Some(OsStr::from_bytes(tag));
+            }
+        }
+
+        None
+    }
+}
+
+
+/// Checks that a property can be represented in a message.
+pub(crate) fn validate_property(name: &OsStr, value: &OsStr) -> ::Result<()> {
+    if name.is_empty() || name.as_bytes().iter().any(|&b| b == b'=' || b == 0) || value.as_bytes().contains(&0) {
+        return Err(::error::from_errno(-::libc::EINVAL));
+    }
+
+    Ok(())
+}
+
+fn parse_properties(bytes: &[u8], base: usize) -> Result<Vec<(OsString, OsString)>, ParseError> {
+    let mut properties = Vec::new();
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        let rest = &bytes[offset..];
+
+        let len = match rest.iter().position(|&b| b == 0) {
+            Some(len) => len,
+            None => return Err(ParseError::MalformedProperty(base + offset)),
+        };
+
+        if len > 0 {
+            let property = &rest[..len];
+
+            match property.iter().position(|&b| b == b'=') {
+                Some(eq) if eq > 0 => {
+                    properties.push((
+                        OsStr::from_bytes(&property[..eq]).to_os_string(),
+                        OsStr::from_bytes(&property[eq + 1..]).to_os_string(),
+                    ));
+                },
+                _ => return Err(ParseError::MalformedProperty(base + offset)),
+            }
+        }
+
+        offset += len + 1;
+    }
+
+    Ok(properties)
+}
+
+fn read_be32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn read_ne32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn write_be32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+}
+
+fn write_ne32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_ne_bytes());
+}
@@ -0,0 +1,396 @@
+//! Typed views of network interfaces.
+//!
+//! Network interfaces are devices in the `net` subsystem. They don't have device nodes; programs
+//! refer to them by name or index. udev's `net_id` builtin computes predictable names for
+//! interfaces from their hardware location and stores them in `ID_NET_NAME_*` properties.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use libudev::net::NetInterface;
+//!
+//! let context = libudev::Context::new().unwrap();
+//!
+//! for interface in NetInterface::interfaces(&context).unwrap() {
+//!     println!("{:?} #{:?} {:?} {:?} path name: {:?}",
+//!              interface.name(),
+//!              interface.ifindex(),
+//!              interface.mac_address().map(|mac| mac.to_string()),
+//!              interface.operstate(),
+//!              interface.name_path());
+//! }
+//! ```
+
+use std::error;
+use std::fmt;
+
+use std::ffi::OsStr;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::{Enumerator, SortOrder};
+use ::usb::UsbDevice;
+
+
+/// A network interface.
+///
+/// A `NetInterface` wraps a device in the `net` subsystem. It dereferences to the underlying
+/// `Device`.
+#[derive(Clone)]
+pub struct NetInterface {
+    device: Device,
+    parent: Option<Device>,
+}
+
+impl NetInterface {
+    /// Creates a network interface view of `device`.
+    ///
+    /// Returns `None` if `device` isn't a network interface.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if device.subsystem() != Some(OsStr::new("net")) {
+            return None;
+        }
+
+        let parent = device.parent();
+
+        Some(NetInterface { device, parent })
+    }
+
+    /// Returns the network interfaces in the system, ordered by interface index.
+    pub fn interfaces(context: &Context) -> ::Result<Vec<NetInterface>> {
+        let mut enumerator = Enumerator::new(context)?;
+
+        enumerator.match_subsystem("net")?;
+
+        let mut interfaces: Vec<NetInterface> = enumerator.scan_devices()?.sorted(SortOrder::Devpath)
+            .into_iter()
+            .filter_map(NetInterface::from_device)
+            .collect();
+
+        interfaces.sort_by_key(|interface| interface.ifindex());
+
+        Ok(interfaces)
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the underlying device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Returns the name of the interface, e.g., `eth0` or `enp3s0`.
+    pub fn name(&self) -> Option<&str> {
+        ::util::value_to_str(self.device.sysname())
+    }
+
+    /// Returns the index of the interface (`ifindex`).
+    pub fn ifindex(&self) -> Option<u32> {
+        ::util::parse_decimal(self.device.attribute_value("ifindex"))
+    }
+
+    /// Returns the hardware address of the interface (`address`).
+    ///
+    /// Returns `None` if the interface doesn't have a 48-bit MAC address. Interfaces without a
+    /// link layer, such as the loopback interface, report an address of all zeros.
+    pub fn mac_address(&self) -> Option<MacAddress> {
+        ::util::value_to_str(self.device.attribute_value("address")).and_then(|address| address.parse().ok())
+    }
+
+    /// Returns the link layer type of the interface (`type`), one of the kernel's `ARPHRD_*`
+    /// constants. Ethernet interfaces have the type 1.
+    pub fn link_type(&self) -> Option<u16> {
+        ::util::parse_decimal(self.device.attribute_value("type"))
+    }
+
+    /// Checks whether the interface's physical link is up (`carrier`).
+    ///
+    /// Returns `None` if the interface is administratively down, in which case the kernel doesn't
+    /// report the carrier state.
+    pub fn carrier(&self) -> Option<bool> {
+        ::util::parse_flag(self.device.attribute_value("carrier"))
+    }
+
+    /// Returns the operational state of the interface (`operstate`).
+    pub fn operstate(&self) -> Option<OperState> {
+        ::util::value_to_str(self.device.attribute_value("operstate")).map(OperState::from_str)
+    }
+
+    /// Returns the maximum transmission unit of the interface in bytes (`mtu`).
+    pub fn mtu(&self) -> Option<u32> {
+        ::util::parse_decimal(self.device.attribute_value("mtu"))
+    }
+
+    /// Returns the flags of the interface (`flags`).
+    pub fn flags(&self) -> Option<InterfaceFlags> {
+        ::util::parse_hex(self.device.attribute_value("flags")).map(InterfaceFlags::from_bits)
+    }
+
+    /// Returns the name that udev computed from the physical location of the interface's
+    /// hardware (`ID_NET_NAME_PATH`), e.g., `enp3s0`.
+    pub fn name_path(&self) -> Option<&str> {
+        self.property("ID_NET_NAME_PATH")
+    }
+
+    /// Returns the name that udev computed from the interface's MAC address (`ID_NET_NAME_MAC`),
+    /// e.g., `enx78e7d1ea46da`.
+    pub fn name_mac(&self) -> Option<&str> {
+        self.property("ID_NET_NAME_MAC")
+    }
+
+    /// Returns the name that udev computed from the firmware's index for an onboard device
+    /// (`ID_NET_NAME_ONBOARD`), e.g., `eno1`.
+    pub fn name_onboard(&self) -> Option<&str> {
+        self.property("ID_NET_NAME_ONBOARD")
+    }
+
+    /// Returns the name that udev computed from the firmware's index for a hotplug slot
+    /// (`ID_NET_NAME_SLOT`), e.g., `ens1`.
+    pub fn name_slot(&self) -> Option<&str> {
+        self.property("ID_NET_NAME_SLOT")
+    }
+
+    /// Checks whether the interface is virtual, i.e., isn't backed by a hardware device.
+    pub fn is_virtual(&self) -> bool {
+        self.parent.is_none()
+    }
+
+    /// Returns the device that backs the interface, such as a PCI function or a USB interface.
+    pub fn hardware(&self) -> Option<&Device> {
+        self.parent.as_ref()
+    }
+
+    /// Returns the name of the driver of the device that backs the interface, e.g., `e1000e` or
+    /// `r8152`.
+    pub fn driver(&self) -> Option<&OsStr> {
+        self.parent.as_ref().and_then(|parent| parent.driver())
+    }
+
+    /// Returns the PCI device that backs the interface, or the PCI host controller of its USB
+    /// device.
+    pub fn pci_device(&self) -> Option<Device> {
+        self.device.parent_with_subsystem("pci")
+    }
+
+    /// Returns the USB device that backs the interface.
+    pub fn usb_device(&self) -> Option<UsbDevice> {
+        self.device.parent_with_subsystem_devtype("usb", "usb_device").and_then(UsbDevice::from_device)
+    }
+
+    fn property(&self, property: &str) -> Option<&str> {
+        ::util::value_to_str(self.device.property_value(property)).filter(|value| !value.is_empty())
+    }
+}
+
+impl Deref for NetInterface {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for NetInterface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NetInterface")
+            .field("name", &self.name())
+            .field("ifindex", &self.ifindex())
+            .field("mac_address", &self.mac_address())
+            .field("operstate", &self.operstate())
+            .finish()
+    }
+}
+
+
+/// The operational state of a network interface, as defined by RFC 2863.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum OperState {
+    /// The state can't be determined.
+    Unknown,
+
+    /// A component of the interface is missing.
+    NotPresent,
+
+    /// The interface can't pass packets.
+    Down,
+
+    /// The interface is down because a lower layer interface is down.
+    LowerLayerDown,
+
+    /// The interface is in a test mode.
+    Testing,
+
+    /// The interface is waiting for an external event, such as 802.1X authentication.
+    Dormant,
+
+    /// The interface can pass packets.
+    Up,
+}
+
+impl OperState {
+    fn from_str(value: &str) -> OperState {
+        match value {
+            "notpresent" => OperState::NotPresent,
+            "down" => OperState::Down,
+            "lowerlayerdown" => OperState::LowerLayerDown,
+            "testing" => OperState::Testing,
+            "dormant" => OperState::Dormant,
+            "up" => OperState::Up,
+            _ => OperState::Unknown,
+        }
+    }
+}
+
+
+/// The flags of a network interface, as reported by the `SIOCGIFFLAGS` ioctl.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct InterfaceFlags {
+    bits: u32,
+}
+
+impl InterfaceFlags {
+    /// Creates a set of flags from the raw `IFF_*` bits.
+    pub fn from_bits(bits: u32) -> Self {
+        InterfaceFlags { bits }
+    }
+
+    /// Returns the raw `IFF_*` bits.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Checks whether all of the given `IFF_*` bits are set.
+    pub fn contains(&self, bits: u32) -> bool {
+        self.bits & bits == bits
+    }
+
+    /// Checks whether the interface is administratively up (`IFF_UP`).
+    pub fn is_up(&self) -> bool {
+        self.contains(::libc::IFF_UP as u32)
+    }
+
+    /// Checks whether the interface is operationally up (`IFF_RUNNING`).
+    pub fn is_running(&self) -> bool {
+        self.contains(::libc::IFF_RUNNING as u32)
+    }
+
+    /// Checks whether the interface is a loopback interface (`IFF_LOOPBACK`).
+    pub fn is_loopback(&self) -> bool {
+        self.contains(::libc::IFF_LOOPBACK as u32)
+    }
+
+    /// Checks whether the interface is a point-to-point link (`IFF_POINTOPOINT`).
+    pub fn is_point_to_point(&self) -> bool {
+        self.contains(::libc::IFF_POINTOPOINT as u32)
+    }
+
+    /// Checks whether the interface supports broadcast (`IFF_BROADCAST`).
+    pub fn is_broadcast(&self) -> bool {
+        self.contains(::libc::IFF_BROADCAST as u32)
+    }
+
+    /// Checks whether the interface supports multicast (`IFF_MULTICAST`).
+    pub fn is_multicast(&self) -> bool {
+        self.contains(::libc::IFF_MULTICAST as u32)
+    }
+
+    /// Checks whether the interface receives all packets (`IFF_PROMISC`).
+    pub fn is_promiscuous(&self) -> bool {
+        self.contains(::libc::IFF_PROMISC as u32)
+    }
+}
+
+
+/// A 48-bit MAC address.
+///
+/// ```
+/// use libudev::net::MacAddress;
+///
+/// let mac: MacAddress = "78:E7:D1:EA:46:DA".parse().unwrap();
+///
+/// assert_eq!(mac.octets(), [0x78, 0xe7, 0xd1, 0xea, 0x46, 0xda]);
+/// assert_eq!(mac.to_string(), "78:e7:d1:ea:46:da");
+/// assert!(!mac.is_multicast());
+/// assert!("78:e7:d1:ea:46".parse::<MacAddress>().is_err());
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct MacAddress {
+    octets: [u8; 6],
+}
+
+impl MacAddress {
+    /// Creates a MAC address from its octets.
+    pub fn new(octets: [u8; 6]) -> Self {
+        MacAddress { octets }
+    }
+
+    /// Returns the octets of the address.
+    pub fn octets(&self) -> [u8; 6] {
+        self.octets
+    }
+
+    /// Checks whether all octets are zero, as reported by interfaces without a link layer.
+    pub fn is_zero(&self) -> bool {
+        self.octets == [0; 6]
+    }
+
+    /// Checks whether the address is a multicast or broadcast address.
+    pub fn is_multicast(&self) -> bool {
+        self.octets[0] & 0x01 != 0
+    }
+
+    /// Checks whether the address was assigned locally rather than by the manufacturer.
+    pub fn is_locally_administered(&self) -> bool {
+        self.octets[0] & 0x02 != 0
+    }
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let o = &self.octets;
+
+        write!(f, "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", o[0], o[1], o[2], o[3], o[4], o[5])
+    }
+}
+
+impl FromStr for MacAddress {
+    type Err = ParseMacAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut octets = [0; 6];
+        let mut parts = s.split(':');
+
+        for octet in octets.iter_mut() {
+            let part = parts.next().ok_or(ParseMacAddressError)?;
+
+            if part.len() != 2 {
+                return Err(ParseMacAddressError);
+            }
+
+            *octet = u8::from_str_radix(part, 16).map_err(|_| ParseMacAddressError)?;
+        }
+
+        if parts.next().is_some() {
+            return Err(ParseMacAddressError);
+        }
+
+        Ok(MacAddress { octets })
+    }
+}
+
+/// An error returned when a string isn't a valid MAC address.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct ParseMacAddressError;
+
+impl fmt::Display for ParseMacAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid MAC address")
+    }
+}
+
+impl error::Error for ParseMacAddressError {}
@@ -0,0 +1,863 @@
+//! Typed views of PCI devices.
+//!
+//! PCI functions are devices in the `pci` subsystem. Their sysnames are their addresses, such as
+//! `0000:03:00.1`, and their configuration space is exposed through attributes like `vendor`,
+//! `device`, and `class`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use libudev::pci::{Class, PciDevice};
+//!
+//! let context = libudev::Context::new().unwrap();
+//!
+//! for pci in PciDevice::devices(&context).unwrap() {
+//!     let class = pci.class_code().unwrap();
+//!
+//!     if class.class() == Class::ProcessingAccelerator {
+//!         println!("{} {:04x}:{:04x} on node {:?}", pci.address().unwrap(), pci.vendor_id().unwrap(), pci.device_id().unwrap(), pci.numa_node());
+//!
+//!         for bar in pci.bars() {
+//!             println!("  BAR {}: {:#x} ({:?} bytes)", bar.index(), bar.start(), bar.size());
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::error;
+use std::fmt;
+
+use std::ffi::OsStr;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::{Enumerator, SortOrder};
+
+
+/// A PCI function.
+///
+/// A `PciDevice` wraps a device in the `pci` subsystem and provides typed access to its
+/// attributes. It dereferences to the underlying `Device`.
+#[derive(Clone)]
+pub struct PciDevice {
+    device: Device,
+}
+
+impl PciDevice {
+    /// Creates a PCI device view of `device`.
+    ///
+    /// Returns `None` if `device` isn't a PCI device.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if device.subsystem() == Some(OsStr::new("pci")) {
+            Some(PciDevice { device })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Finds the PCI device that `device` belongs to.
+    ///
+    /// Returns `device` itself if it's a PCI device, otherwise its closest PCI ancestor.
+    pub fn find(device: &Device) -> Option<Self> {
+        if device.subsystem() == Some(OsStr::new("pci")) {
+            return Some(PciDevice { device: device.clone() });
+        }
+
+        device.parent_with_subsystem("pci").map(|device| PciDevice { device })
+    }
+
+    /// Returns the PCI devices in the system, ordered by address.
+    pub fn devices(context: &Context) -> ::Result<Vec<PciDevice>> {
+        let mut enumerator = Enumerator::new(context)?;
+
+        enumerator.match_subsystem("pci")?;
+
+        let mut devices: Vec<PciDevice> = enumerator.scan_devices()?.sorted(SortOrder::Devpath)
+            .into_iter()
+            .filter_map(PciDevice::from_device)
+            .collect();
+
+        devices.sort_by_key(|pci| pci.address());
+
+        Ok(devices)
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the underlying device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Returns the address of the function, parsed from its sysname.
+    pub fn address(&self) -> Option<PciAddress> {
+        ::util::value_to_str(self.device.sysname()).and_then(|sysname| sysname.parse().ok())
+    }
+
+    /// Returns the vendor ID (`vendor`).
+    pub fn vendor_id(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("vendor"))
+    }
+
+    /// Returns the device ID (`device`).
+    pub fn device_id(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("device"))
+    }
+
+    /// Returns the subsystem vendor ID (`subsystem_vendor`), which identifies the maker of the
+    /// board or card.
+    pub fn subsystem_vendor_id(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("subsystem_vendor"))
+    }
+
+    /// Returns the subsystem device ID (`subsystem_device`).
+    pub fn subsystem_device_id(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("subsystem_device"))
+    }
+
+    /// Returns the revision ID (`revision`).
+    pub fn revision(&self) -> Option<u8> {
+        ::util::parse_hex(self.device.attribute_value("revision"))
+    }
+
+    /// Returns the class code (`class`).
+    pub fn class_code(&self) -> Option<ClassCode> {
+        ::util::parse_hex(self.device.attribute_value("class")).map(ClassCode::from_u32)
+    }
+
+    /// Returns the NUMA node that the device is attached to (`numa_node`).
+    ///
+    /// Returns `None` on systems without NUMA, where the kernel reports -1.
+    pub fn numa_node(&self) -> Option<u32> {
+        ::util::parse_decimal::<i32>(self.device.attribute_value("numa_node"))
+            .and_then(|node| if node >= 0 { Some(node as u32) } else { None })
+    }
+
+    /// Returns the base address registers of the function that are in use, from the `resource`
+    /// attribute.
+    pub fn bars(&self) -> Vec<Bar> {
+        self.resources().into_iter().filter(|bar| bar.index < 6).collect()
+    }
+
+    /// Returns the expansion ROM of the function, if it has one.
+    pub fn rom(&self) -> Option<Bar> {
+        self.resources().into_iter().find(|bar| bar.index == 6)
+    }
+
+    /// Returns the maximum number of SR-IOV virtual functions that the device supports
+    /// (`sriov_totalvfs`).
+    ///
+    /// Returns `None` if the device doesn't support SR-IOV.
+    pub fn sriov_total_vfs(&self) -> Option<u32> {
+        ::util::parse_decimal(self.device.attribute_value("sriov_totalvfs"))
+    }
+
+    /// Returns the number of SR-IOV virtual functions that are enabled (`sriov_numvfs`).
+    ///
+    /// Returns `None` if the device doesn't support SR-IOV.
+    pub fn sriov_num_vfs(&self) -> Option<u32> {
+        ::util::parse_decimal(self.device.attribute_value("sriov_numvfs"))
+    }
+
+    /// Returns the enabled SR-IOV virtual functions of the device, ordered by index.
+    ///
+    /// Returns an empty list if the device doesn't support SR-IOV.
+    pub fn virtual_functions(&self) -> ::Result<Vec<PciDevice>> {
+        let syspath = match self.device.syspath() {
+            Some(syspath) => syspath,
+            None => return Ok(Vec::new()),
+        };
+
+        let context = self.device.context();
+        let mut functions = Vec::new();
+
+        for index in 0..self.sriov_num_vfs().unwrap_or(0) {
+            let link = syspath.join(format!("virtfn{}", index));
+
+            // The number of functions may have changed since `sriov_numvfs` was read.
+            if !link.exists() {
+                break;
+            }
+
+            let device = Device::from_syspath(&context, &link)?;
+            functions.extend(PciDevice::from_device(device));
+        }
+
+        Ok(functions)
+    }
+
+    /// Returns the physical function of an SR-IOV virtual function.
+    pub fn physical_function(&self) -> Option<PciDevice> {
+        let link = self.device.syspath()?.join("physfn");
+
+        if !link.exists() {
+            return None;
+        }
+
+        Device::from_syspath(&self.device.context(), &link).ok().and_then(PciDevice::from_device)
+    }
+
+    /// Checks whether the device is an SR-IOV virtual function.
+    pub fn is_virtual_function(&self) -> bool {
+        self.device.syspath().is_some_and(|syspath| syspath.join("physfn").exists())
+    }
+
+    fn resources(&self) -> Vec<Bar> {
+        let resource = match ::util::value_to_str(self.device.attribute_value("resource")) {
+            Some(resource) => resource,
+            None => return Vec::new(),
+        };
+
+        resource.lines().enumerate().filter_map(|(index, line)| Bar::parse(index, line)).collect()
+    }
+}
+
+impl Deref for PciDevice {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for PciDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PciDevice")
+            .field("address", &self.address())
+            .field("vendor_id", &self.vendor_id())
+            .field("device_id", &self.device_id())
+            .field("class_code", &self.class_code())
+            .finish()
+    }
+}
+
+
+/// The address of a PCI function: `domain:bus:device.function`.
+///
+/// ```
+/// use libudev::pci::PciAddress;
+///
+/// let address: PciAddress = "0000:3b:00.1".parse().unwrap();
+///
+/// assert_eq!(address.domain(), 0);
+/// assert_eq!(address.bus(), 0x3b);
+/// assert_eq!(address.device(), 0);
+/// assert_eq!(address.function(), 1);
+/// assert_eq!(address.to_string(), "0000:3b:00.1");
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct PciAddress {
+    domain: u32,
+    bus: u8,
+    device: u8,
+    function: u8,
+}
+
+impl PciAddress {
+    /// Creates an address from its parts.
+    pub fn new(domain: u32, bus: u8, device: u8, function: u8) -> Self {
+        PciAddress { domain, bus, device, function }
+    }
+
+    /// Returns the PCI domain, also called segment.
+    pub fn domain(&self) -> u32 {
+        self.domain
+    }
+
+    /// Returns the bus number.
+    pub fn bus(&self) -> u8 {
+        self.bus
+    }
+
+    /// Returns the device number on the bus.
+    pub fn device(&self) -> u8 {
+        self.device
+    }
+
+    /// Returns the function number of the device.
+    pub fn function(&self) -> u8 {
+        self.function
+    }
+}
+
+impl fmt::Display for PciAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04x}:{:02x}:{:02x}.{:x}", self.domain, self.bus, self.device, self.function)
+    }
+}
+
+impl FromStr for PciAddress {
+    type Err = ParsePciAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.rsplitn(3, ':');
+
+        let (slot, bus, domain) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(slot), Some(bus), Some(domain)) => (slot, bus, domain),
+            _ => return Err(ParsePciAddressError),
+        };
+
+        let (device, function) = slot.split_once('.').ok_or(ParsePciAddressError)?;
+
+        let domain = u32::from_str_radix(domain, 16).map_err(|_| ParsePciAddressError)?;
+        let bus = u8::from_str_radix(bus, 16).map_err(|_| ParsePciAddressError)?;
+        let device = u8::from_str_radix(device, 16).map_err(|_| ParsePciAddressError)?;
+        let function = u8::from_str_radix(function, 16).map_err(|_| ParsePciAddressError)?;
+
+        if device > 0x1f || function > 7 {
+            return Err(ParsePciAddressError);
+        }
+
+        Ok(PciAddress::new(domain, bus, device, function))
+    }
+}
+
+/// An error returned when a string isn't a valid PCI address.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct ParsePciAddressError;
+
+impl fmt::Display for ParsePciAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid PCI address")
+    }
+}
+
+impl error::Error for ParsePciAddressError {}
+
+
+/// A base address register of a PCI function, or its expansion ROM.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Bar {
+    index: usize,
+    start: u64,
+    end: u64,
+    flags: u64,
+}
+
+// Resource flags from the kernel's `include/linux/ioport.h`.
+const IORESOURCE_IO: u64 = 0x0000_0100;
+const IORESOURCE_MEM: u64 = 0x0000_0200;
+const IORESOURCE_PREFETCH: u64 = 0x0000_2000;
+const IORESOURCE_MEM_64: u64 = 0x0010_0000;
+
+impl Bar {
+    /// Parses a line of the `resource` attribute: the start address, end address, and flags.
+    /// Unused entries are all zeros.
+    fn parse(index: usize, line: &str) -> Option<Bar> {
+        let mut values = line.split_whitespace().map(|value| ::util::parse_hex::<u64>(Some(OsStr::new(value))));
+
+        let bar = Bar {
+            index,
+            start: values.next()??,
+            end: values.next()??,
+            flags: values.next()??,
+        };
+
+        if bar.flags == 0 && bar.end == 0 {
+            None
+        }
+        else {
+            Some(bar)
+        }
+    }
+
+    /// Returns the index of the register. Indices 0 to 5 are BARs and 6 is the expansion ROM.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the first address of the region.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the last address of the region.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the size of the region in bytes.
+    ///
+    /// Returns `None` if the end address comes before the start address or the region covers the
+    /// whole 64-bit address space, whose size doesn't fit in a `u64`.
+    pub fn size(&self) -> Option<u64> {
+        self.end.checked_sub(self.start).and_then(|size| size.checked_add(1))
+    }
+
+    /// Returns the kernel's `IORESOURCE_*` flags for the region.
+    pub fn flags(&self) -> u64 {
+        self.flags
+    }
+
+    /// Checks whether the region is in I/O port space.
+    pub fn is_io(&self) -> bool {
+        self.flags & IORESOURCE_IO != 0
+    }
+
+    /// Checks whether the region is in memory space.
+    pub fn is_memory(&self) -> bool {
+        self.flags & IORESOURCE_MEM != 0
+    }
+
+    /// Checks whether the memory region is prefetchable.
+    pub fn is_prefetchable(&self) -> bool {
+        self.flags & IORESOURCE_PREFETCH != 0
+    }
+
+    /// Checks whether the memory region is decoded with 64-bit addresses.
+    pub fn is_64bit(&self) -> bool {
+        self.flags & IORESOURCE_MEM_64 != 0
+    }
+}
+
+
+/// A PCI class code: the base class, subclass, and programming interface of a function.
+///
+/// ```
+/// use libudev::pci::{Class, ClassCode, ProgIf, Subclass};
+///
+/// let code = ClassCode::from_u32(0x0c0330);
+///
+/// assert_eq!(code.class(), Class::SerialBus);
+/// assert_eq!(code.subclass(), Subclass::Usb);
+/// assert_eq!(code.prog_if(), ProgIf::Xhci);
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct ClassCode {
+    class: u8,
+    subclass: u8,
+    prog_if: u8,
+}
+
+impl ClassCode {
+    /// Creates a class code from its parts.
+    pub fn new(class: u8, subclass: u8, prog_if: u8) -> Self {
+        ClassCode { class, subclass, prog_if }
+    }
+
+    /// Creates a class code from the 24-bit value of the `class` attribute.
+    pub fn from_u32(value: u32) -> Self {
+        ClassCode::new((value >> 16) as u8, (value >> 8) as u8, value as u8)
+    }
+
+    /// Returns the 24-bit value of the class code.
+    pub fn to_u32(&self) -> u32 {
+        (self.class as u32) << 16 | (self.subclass as u32) << 8 | self.prog_if as u32
+    }
+
+    /// Returns the base class.
+    pub fn class(&self) -> Class {
+        Class::from_u8(self.class)
+    }
+
+    /// Returns the subclass.
+    pub fn subclass(&self) -> Subclass {
+        Subclass::from_u8(self.class, self.subclass)
+    }
+
+    /// Returns the programming interface.
+    pub fn prog_if(&self) -> ProgIf {
+        ProgIf::from_u8(self.class, self.subclass, self.prog_if)
+    }
+
+    /// Returns the raw base class.
+    pub fn class_id(&self) -> u8 {
+        self.class
+    }
+
+    /// Returns the raw subclass.
+    pub fn subclass_id(&self) -> u8 {
+        self.subclass
+    }
+
+    /// Returns the raw programming interface.
+    pub fn prog_if_id(&self) -> u8 {
+        self.prog_if
+    }
+}
+
+/// The base class of a PCI function.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Class {
+    /// A device built before class codes were defined.
+    Unclassified,
+
+    /// A mass storage controller, such as a SATA or NVMe controller.
+    MassStorage,
+
+    /// A network controller.
+    Network,
+
+    /// A display controller.
+    Display,
+
+    /// A multimedia controller, such as an audio or video device.
+    Multimedia,
+
+    /// A memory controller.
+    Memory,
+
+    /// A bridge between buses.
+    Bridge,
+
+    /// A simple communication controller, such as a serial port or modem.
+    Communication,
+
+    /// A base system peripheral, such as an interrupt controller or IOMMU.
+    SystemPeripheral,
+
+    /// An input device controller.
+    Input,
+
+    /// A docking station.
+    DockingStation,
+
+    /// A processor.
+    Processor,
+
+    /// A serial bus controller, such as a USB or SMBus controller.
+    SerialBus,
+
+    /// A wireless controller.
+    Wireless,
+
+    /// An intelligent I/O controller.
+    IntelligentIo,
+
+    /// A satellite communication controller.
+    SatelliteCommunication,
+
+    /// An encryption controller.
+    Encryption,
+
+    /// A signal processing controller.
+    SignalProcessing,
+
+    /// A processing accelerator.
+    ProcessingAccelerator,
+
+    /// A non-essential instrumentation function.
+    NonEssentialInstrumentation,
+
+    /// A coprocessor.
+    Coprocessor,
+
+    /// A device that doesn't fit any of the defined classes, reported as `0xff`.
+    Unassigned,
+
+    /// A class that this crate doesn't know about.
+    Other(u8),
+}
+
+impl Class {
+    fn from_u8(class: u8) -> Class {
+        match class {
+            0x00 => Class::Unclassified,
+            0x01 => Class::MassStorage,
+            0x02 => Class::Network,
+            0x03 => Class::Display,
+            0x04 => Class::Multimedia,
+            0x05 => Class::Memory,
+            0x06 => Class::Bridge,
+            0x07 => Class::Communication,
+            0x08 => Class::SystemPeripheral,
+            0x09 => Class::Input,
+            0x0a => Class::DockingStation,
+            0x0b => Class::Processor,
+            0x0c => Class::SerialBus,
+            0x0d => Class::Wireless,
+            0x0e => Class::IntelligentIo,
+            0x0f => Class::SatelliteCommunication,
+            0x10 => Class::Encryption,
+            0x11 => Class::SignalProcessing,
+            0x12 => Class::ProcessingAccelerator,
+            0x13 => Class::NonEssentialInstrumentation,
+            0x40 => Class::Coprocessor,
+            0xff => Class::Unassigned,
+            _ => Class::Other(class),
+        }
+    }
+}
+
+/// The subclass of a PCI function, interpreted in the context of its base class.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Subclass {
+    /// A SCSI storage controller.
+    Scsi,
+
+    /// An IDE interface.
+    Ide,
+
+    /// A floppy disk controller.
+    Floppy,
+
+    /// A RAID controller.
+    Raid,
+
+    /// An ATA controller.
+    Ata,
+
+    /// A SATA controller.
+    Sata,
+
+    /// A Serial Attached SCSI controller.
+    Sas,
+
+    /// A non-volatile memory controller, such as an NVMe controller.
+    NonVolatileMemory,
+
+    /// A Universal Flash Storage controller.
+    Ufs,
+
+    /// An Ethernet controller.
+    Ethernet,
+
+    /// A Token Ring controller.
+    TokenRing,
+
+    /// An FDDI controller.
+    Fddi,
+
+    /// An ATM controller.
+    Atm,
+
+    /// An ISDN controller.
+    Isdn,
+
+    /// An InfiniBand controller.
+    Infiniband,
+
+    /// A host fabric controller.
+    Fabric,
+
+    /// A VGA-compatible display controller.
+    Vga,
+
+    /// An XGA display controller.
+    Xga,
+
+    /// A 3D controller that isn't VGA-compatible.
+    Display3d,
+
+    /// A multimedia video controller.
+    Video,
+
+    /// A multimedia audio controller.
+    Audio,
+
+    /// A computer telephony device.
+    Telephony,
+
+    /// A High Definition Audio controller.
+    HdAudio,
+
+    /// A RAM controller.
+    Ram,
+
+    /// A flash memory controller.
+    Flash,
+
+    /// A CXL memory device.
+    Cxl,
+
+    /// A host bridge.
+    HostBridge,
+
+    /// A PCI-to-ISA bridge.
+    IsaBridge,
+
+    /// A PCI-to-EISA bridge.
+    EisaBridge,
+
+    /// A PCI-to-PCI bridge.
+    PciBridge,
+
+    /// A PCI-to-PCMCIA bridge.
+    PcmciaBridge,
+
+    /// A PCI-to-CardBus bridge.
+    CardBusBridge,
+
+    /// A semi-transparent PCI-to-PCI bridge.
+    SemiTransparentPciBridge,
+
+    /// A serial controller.
+    SerialController,
+
+    /// A parallel port controller.
+    ParallelController,
+
+    /// A modem.
+    Modem,
+
+    /// An interrupt controller.
+    InterruptController,
+
+    /// A DMA controller.
+    DmaController,
+
+    /// A system timer.
+    Timer,
+
+    /// A real-time clock controller.
+    Rtc,
+
+    /// An SD host controller.
+    SdHost,
+
+    /// An IOMMU.
+    Iommu,
+
+    /// A FireWire (IEEE 1394) controller.
+    FireWire,
+
+    /// A USB controller.
+    Usb,
+
+    /// A Fibre Channel controller.
+    FibreChannel,
+
+    /// An SMBus controller.
+    Smbus,
+
+    /// An IPMI interface.
+    Ipmi,
+
+    /// A Bluetooth controller.
+    Bluetooth,
+
+    /// A Wi-Fi controller.
+    Wlan,
+
+    /// A subclass without a specific meaning, reported as `0x80` in most classes.
+    Misc,
+
+    /// A subclass that this crate doesn't know about.
+    Other(u8),
+}
+
+impl Subclass {
+    fn from_u8(class: u8, subclass: u8) -> Subclass {
+        match (class, subclass) {
+            (0x01, 0x00) => Subclass::Scsi,
+            (0x01, 0x01) => Subclass::Ide,
+            (0x01, 0x02) => Subclass::Floppy,
+            (0x01, 0x04) => Subclass::Raid,
+            (0x01, 0x05) => Subclass::Ata,
+            (0x01, 0x06) => Subclass::Sata,
+            (0x01, 0x07) => Subclass::Sas,
+            (0x01, 0x08) => Subclass::NonVolatileMemory,
+            (0x01, 0x09) => Subclass::Ufs,
+
+            (0x02, 0x00) => Subclass::Ethernet,
+            (0x02, 0x01) => Subclass::TokenRing,
+            (0x02, 0x02) => Subclass::Fddi,
+            (0x02, 0x03) => Subclass::Atm,
+            (0x02, 0x04) => Subclass::Isdn,
+            (0x02, 0x07) => Subclass::Infiniband,
+            (0x02, 0x08) => Subclass::Fabric,
+
+            (0x03, 0x00) => Subclass::Vga,
+            (0x03, 0x01) => Subclass::Xga,
+            (0x03, 0x02) => Subclass::Display3d,
+
+            (0x04, 0x00) => Subclass::Video,
+            (0x04, 0x01) => Subclass::Audio,
+            (0x04, 0x02) => Subclass::Telephony,
+            (0x04, 0x03) => Subclass::HdAudio,
+
+            (0x05, 0x00) => Subclass::Ram,
+            (0x05, 0x01) => Subclass::Flash,
+            (0x05, 0x02) => Subclass::Cxl,
+
+            (0x06, 0x00) => Subclass::HostBridge,
+            (0x06, 0x01) => Subclass::IsaBridge,
+            (0x06, 0x02) => Subclass::EisaBridge,
+            (0x06, 0x04) => Subclass::PciBridge,
+            (0x06, 0x05) => Subclass::PcmciaBridge,
+            (0x06, 0x07) => Subclass::CardBusBridge,
+            (0x06, 0x09) => Subclass::SemiTransparentPciBridge,
+
+            (0x07, 0x00) => Subclass::SerialController,
+            (0x07, 0x01) => Subclass::ParallelController,
+            (0x07, 0x03) => Subclass::Modem,
+
+            (0x08, 0x00) => Subclass::InterruptController,
+            (0x08, 0x01) => Subclass::DmaController,
+            (0x08, 0x02) => Subclass::Timer,
+            (0x08, 0x03) => Subclass::Rtc,
+            (0x08, 0x05) => Subclass::SdHost,
+            (0x08, 0x06) => Subclass::Iommu,
+
+            (0x0c, 0x00) => Subclass::FireWire,
+            (0x0c, 0x03) => Subclass::Usb,
+            (0x0c, 0x04) => Subclass::FibreChannel,
+            (0x0c, 0x05) => Subclass::Smbus,
+            (0x0c, 0x07) => Subclass::Ipmi,
+
+            (0x0d, 0x11) => Subclass::Bluetooth,
+            (0x0d, 0x20) | (0x0d, 0x21) => Subclass::Wlan,
+
+            (_, 0x80) => Subclass::Misc,
+            _ => Subclass::Other(subclass),
+        }
+    }
+}
+
+/// The programming interface of a PCI function, interpreted in the context of its class and
+/// subclass.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum ProgIf {
+    /// A VGA-compatible display controller.
+    VgaCompatible,
+
+    /// An AHCI SATA controller.
+    Ahci,
+
+    /// An NVM Express controller.
+    Nvme,
+
+    /// A USB 1.1 UHCI host controller.
+    Uhci,
+
+    /// A USB 1.1 OHCI host controller.
+    Ohci,
+
+    /// A USB 2.0 EHCI host controller.
+    Ehci,
+
+    /// A USB 3 xHCI host controller.
+    Xhci,
+
+    /// A USB device controller.
+    UsbDevice,
+
+    /// A 16550-compatible serial controller.
+    Uart16550,
+
+    /// A programming interface that this crate doesn't know about.
+    Other(u8),
+}
+
+impl ProgIf {
+    fn from_u8(class: u8, subclass: u8, prog_if: u8) -> ProgIf {
+        match (class, subclass, prog_if) {
+            (0x03, 0x00, 0x00) => ProgIf::VgaCompatible,
+            (0x01, 0x06, 0x01) => ProgIf::Ahci,
+            (0x01, 0x08, 0x02) => ProgIf::Nvme,
+            (0x0c, 0x03, 0x00) => ProgIf::Uhci,
+            (0x0c, 0x03, 0x10) => ProgIf::Ohci,
+            (0x0c, 0x03, 0x20) => ProgIf::Ehci,
+            (0x0c, 0x03, 0x30) => ProgIf::Xhci,
+            (0x0c, 0x03, 0xfe) => ProgIf::UsbDevice,
+            (0x07, 0x00, 0x02) => ProgIf::Uart16550,
+            _ => ProgIf::Other(prog_if),
+        }
+    }
+}
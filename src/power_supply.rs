@@ -0,0 +1,455 @@
+//! Typed views of power supplies and batteries.
+//!
+//! Power supplies are devices in the `power_supply` subsystem: batteries, AC adapters (mains),
+//! USB ports that can supply power, and UPSes. The kernel reports their state both as attributes
+//! and as `POWER_SUPPLY_*` properties, which are also included in the `change` events it sends
+//! when the state changes. The views read the properties and fall back to the attributes.
+//!
+//! Energies, charges, voltages, currents, and powers are reported by the kernel in µWh, µAh, µV,
+//! µA, and µW. The views convert them to Wh, Ah, V, A, and W.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use libudev::power_supply::{self, Battery};
+//!
+//! let context = libudev::Context::new().unwrap();
+//!
+//! for battery in Battery::batteries(&context).unwrap() {
+//!     println!("{:?}: {:?}% {:?}", battery.name(), battery.capacity(), battery.status());
+//! }
+//!
+//! let mut socket = power_supply::monitor(&context).unwrap();
+//!
+//! // Wait for the socket to become readable with poll(), then:
+//! for update in power_supply::battery_updates(&mut socket) {
+//!     println!("{:?}: {:?} Wh", update.battery().name(), update.battery().energy_now());
+//! }
+//! ```
+
+use std::fmt;
+
+use std::ffi::OsStr;
+use std::ops::Deref;
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::{Enumerator, SortOrder};
+use ::monitor::{Monitor, MonitorSocket, EventType};
+
+
+/// Creates a monitor socket that receives events for power supplies.
+pub fn monitor(context: &Context) -> ::Result<MonitorSocket> {
+    let mut monitor = Monitor::new(context)?;
+
+    monitor.match_subsystem("power_supply")?;
+
+    monitor.listen()
+}
+
+/// Returns an iterator over the battery events that are available on `socket`.
+///
+/// Events for other devices are skipped. Like `MonitorSocket::receive_event()`, the iterator
+/// doesn't block: it ends when no more events are available. Use `monitor()` to create a socket
+/// that only receives power supply events.
+pub fn battery_updates(socket: &mut MonitorSocket) -> BatteryUpdates<'_> {
+    BatteryUpdates { socket }
+}
+
+
+/// A power supply.
+///
+/// A `PowerSupply` wraps a device in the `power_supply` subsystem. It dereferences to the
+/// underlying `Device`.
+#[derive(Clone)]
+pub struct PowerSupply {
+    device: Device,
+}
+
+impl PowerSupply {
+    /// Creates a power supply view of `device`.
+    ///
+    /// Returns `None` if `device` isn't a power supply.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if device.subsystem() == Some(OsStr::new("power_supply")) {
+            Some(PowerSupply { device })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the power supplies in the system, ordered by sysname.
+    pub fn supplies(context: &Context) -> ::Result<Vec<PowerSupply>> {
+        let mut enumerator = Enumerator::new(context)?;
+
+        enumerator.match_subsystem("power_supply")?;
+
+        let devices = enumerator.scan_devices()?.sorted(SortOrder::Sysnum);
+
+        Ok(devices.into_iter().filter_map(PowerSupply::from_device).collect())
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the underlying device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Returns the name of the power supply, e.g., `BAT0` or `AC`.
+    pub fn name(&self) -> Option<&str> {
+        self.string("NAME").or_else(|| ::util::value_to_str(self.device.sysname()))
+    }
+
+    /// Returns the kind of power supply.
+    pub fn supply_type(&self) -> Option<SupplyType> {
+        self.string("TYPE").map(SupplyType::from_str)
+    }
+
+    /// Returns a battery view of the power supply, or `None` if it isn't a battery.
+    pub fn as_battery(&self) -> Option<Battery> {
+        if self.supply_type() == Some(SupplyType::Battery) {
+            Some(Battery { supply: self.clone() })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Checks whether the power supply is connected and supplying power (`online`).
+    ///
+    /// This applies to mains, USB, and wireless power supplies.
+    pub fn is_online(&self) -> Option<bool> {
+        self.value("ONLINE").and_then(|online| ::util::parse_decimal::<u32>(Some(online))).map(|online| online != 0)
+    }
+
+    /// Checks whether the power supply is present (`present`), e.g., whether a battery is
+    /// inserted.
+    pub fn is_present(&self) -> Option<bool> {
+        ::util::parse_flag(self.value("PRESENT"))
+    }
+
+    /// Returns the manufacturer of the power supply.
+    pub fn manufacturer(&self) -> Option<&str> {
+        self.string("MANUFACTURER")
+    }
+
+    /// Returns the model name of the power supply.
+    pub fn model_name(&self) -> Option<&str> {
+        self.string("MODEL_NAME")
+    }
+
+    /// Returns the serial number of the power supply.
+    pub fn serial_number(&self) -> Option<&str> {
+        self.string("SERIAL_NUMBER")
+    }
+
+    /// Returns the value of a `POWER_SUPPLY_*` property, or of the corresponding attribute if the
+    /// property isn't set.
+    ///
+    /// `name` is the name without the prefix, e.g., `CAPACITY` for `POWER_SUPPLY_CAPACITY` or the
+    /// `capacity` attribute.
+    pub fn value(&self, name: &str) -> Option<&OsStr> {
+        self.device.property_value(format!("POWER_SUPPLY_{}", name))
+            .or_else(|| self.device.attribute_value(name.to_lowercase()))
+    }
+
+    fn string(&self, name: &str) -> Option<&str> {
+        ::util::value_to_str(self.value(name)).filter(|value| !value.is_empty())
+    }
+
+    /// Reads a value in millionths of a unit and converts it to the unit.
+    fn micro(&self, name: &str) -> Option<f64> {
+        ::util::parse_decimal::<i64>(self.value(name)).map(|value| value as f64 / 1_000_000.0)
+    }
+}
+
+impl Deref for PowerSupply {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for PowerSupply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PowerSupply")
+            .field("name", &self.name())
+            .field("supply_type", &self.supply_type())
+            .field("online", &self.is_online())
+            .finish()
+    }
+}
+
+
+/// A kind of power supply.
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub enum SupplyType {
+    /// A battery.
+    Battery,
+
+    /// An uninterruptible power supply.
+    Ups,
+
+    /// An AC adapter.
+    Mains,
+
+    /// A USB port that supplies power.
+    Usb,
+
+    /// A wireless charger.
+    Wireless,
+
+    /// A kind that this crate doesn't know about.
+    Other(String),
+}
+
+impl SupplyType {
+    fn from_str(value: &str) -> SupplyType {
+        match value {
+            "Battery" => SupplyType::Battery,
+            "UPS" => SupplyType::Ups,
+            "Mains" => SupplyType::Mains,
+            "USB" => SupplyType::Usb,
+            "Wireless" => SupplyType::Wireless,
+            _ => SupplyType::Other(value.to_owned()),
+        }
+    }
+}
+
+
+/// A battery.
+///
+/// Batteries report their state either as energy (Wh) or as charge (Ah), depending on the
+/// driver, so either the `energy_*` or the `charge_*` methods return values. A `Battery`
+/// dereferences to a `PowerSupply`.
+#[derive(Clone)]
+pub struct Battery {
+    supply: PowerSupply,
+}
+
+impl Battery {
+    /// Creates a battery view of `device`.
+    ///
+    /// Returns `None` if `device` isn't a battery.
+    pub fn from_device(device: Device) -> Option<Self> {
+        PowerSupply::from_device(device).and_then(|supply| supply.as_battery())
+    }
+
+    /// Returns the batteries in the system, ordered by sysname.
+    pub fn batteries(context: &Context) -> ::Result<Vec<Battery>> {
+        let supplies = PowerSupply::supplies(context)?;
+
+        Ok(supplies.iter().filter_map(PowerSupply::as_battery).collect())
+    }
+
+    /// Returns the power supply view of the battery.
+    pub fn power_supply(&self) -> &PowerSupply {
+        &self.supply
+    }
+
+    /// Returns the charging status of the battery.
+    pub fn status(&self) -> Option<BatteryStatus> {
+        self.supply.string("STATUS").map(BatteryStatus::from_str)
+    }
+
+    /// Returns the remaining capacity in percent.
+    pub fn capacity(&self) -> Option<u8> {
+        ::util::parse_decimal(self.supply.value("CAPACITY"))
+    }
+
+    /// Returns a coarse capacity level, e.g., `Normal`, `Low`, or `Critical`, for batteries that
+    /// don't report a percentage.
+    pub fn capacity_level(&self) -> Option<&str> {
+        self.supply.string("CAPACITY_LEVEL")
+    }
+
+    /// Returns the energy stored in the battery in Wh.
+    pub fn energy_now(&self) -> Option<f64> {
+        self.supply.micro("ENERGY_NOW")
+    }
+
+    /// Returns the energy stored in the battery when it was last fully charged, in Wh.
+    pub fn energy_full(&self) -> Option<f64> {
+        self.supply.micro("ENERGY_FULL")
+    }
+
+    /// Returns the energy the battery was designed to store, in Wh.
+    pub fn energy_full_design(&self) -> Option<f64> {
+        self.supply.micro("ENERGY_FULL_DESIGN")
+    }
+
+    /// Returns the charge stored in the battery in Ah.
+    pub fn charge_now(&self) -> Option<f64> {
+        self.supply.micro("CHARGE_NOW")
+    }
+
+    /// Returns the charge stored in the battery when it was last fully charged, in Ah.
+    pub fn charge_full(&self) -> Option<f64> {
+        self.supply.micro("CHARGE_FULL")
+    }
+
+    /// Returns the charge the battery was designed to store, in Ah.
+    pub fn charge_full_design(&self) -> Option<f64> {
+        self.supply.micro("CHARGE_FULL_DESIGN")
+    }
+
+    /// Returns the voltage of the battery in V.
+    pub fn voltage_now(&self) -> Option<f64> {
+        self.supply.micro("VOLTAGE_NOW")
+    }
+
+    /// Returns the current flowing out of or into the battery in A.
+    pub fn current_now(&self) -> Option<f64> {
+        self.supply.micro("CURRENT_NOW")
+    }
+
+    /// Returns the power drawn from or supplied to the battery in W.
+    pub fn power_now(&self) -> Option<f64> {
+        self.supply.micro("POWER_NOW")
+    }
+
+    /// Returns the ratio of the full capacity to the design capacity, from 0 to 1.
+    pub fn health_ratio(&self) -> Option<f64> {
+        match (self.energy_full(), self.energy_full_design()) {
+            (Some(full), Some(design)) if design > 0.0 => Some(full / design),
+            _ => match (self.charge_full(), self.charge_full_design()) {
+                (Some(full), Some(design)) if design > 0.0 => Some(full / design),
+                _ => None,
+            },
+        }
+    }
+
+    /// Returns the number of charge cycles the battery has gone through.
+    pub fn cycle_count(&self) -> Option<u32> {
+        ::util::parse_decimal(self.supply.value("CYCLE_COUNT"))
+    }
+
+    /// Returns the chemistry of the battery, e.g., `Li-ion`.
+    pub fn technology(&self) -> Option<&str> {
+        self.supply.string("TECHNOLOGY")
+    }
+
+    /// Returns the health reported by the battery, e.g., `Good` or `Overheat`.
+    pub fn health(&self) -> Option<&str> {
+        self.supply.string("HEALTH")
+    }
+}
+
+impl Deref for Battery {
+    type Target = PowerSupply;
+
+    fn deref(&self) -> &PowerSupply {
+        &self.supply
+    }
+}
+
+impl fmt::Debug for Battery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Battery")
+            .field("name", &self.name())
+            .field("status", &self.status())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+
+/// The charging status of a battery.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum BatteryStatus {
+    /// The battery is charging.
+    Charging,
+
+    /// The battery is discharging.
+    Discharging,
+
+    /// The battery is connected to power but not charging, e.g., because of a charge threshold.
+    NotCharging,
+
+    /// The battery is fully charged.
+    Full,
+
+    /// The status can't be determined.
+    Unknown,
+}
+
+impl BatteryStatus {
+    fn from_str(value: &str) -> BatteryStatus {
+        match value {
+            "Charging" => BatteryStatus::Charging,
+            "Discharging" => BatteryStatus::Discharging,
+            "Not charging" => BatteryStatus::NotCharging,
+            "Full" => BatteryStatus::Full,
+            _ => BatteryStatus::Unknown,
+        }
+    }
+}
+
+
+/// An event for a battery.
+pub struct BatteryUpdate {
+    event_type: EventType,
+    sequence_number: u64,
+    battery: Battery,
+}
+
+impl BatteryUpdate {
+    /// Returns the type of the event.
+    pub fn event_type(&self) -> EventType {
+        self.event_type
+    }
+
+    /// Returns the sequence number of the event.
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    /// Returns the state of the battery as reported by the event.
+    pub fn battery(&self) -> &Battery {
+        &self.battery
+    }
+
+    /// Consumes the update and returns the battery.
+    pub fn into_battery(self) -> Battery {
+        self.battery
+    }
+}
+
+impl fmt::Debug for BatteryUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BatteryUpdate")
+            .field("event_type", &self.event_type)
+            .field("sequence_number", &self.sequence_number)
+            .field("battery", &self.battery)
+            .finish()
+    }
+}
+
+/// Iterator over the battery events that are available on a monitor socket.
+pub struct BatteryUpdates<'a> {
+    socket: &'a mut MonitorSocket,
+}
+
+impl<'a> Iterator for BatteryUpdates<'a> {
+    type Item = BatteryUpdate;
+
+    fn next(&mut self) -> Option<BatteryUpdate> {
+        while let Some(event) = self.socket.receive_event() {
+            if let Some(battery) = Battery::from_device(event.device().clone()) {
+                return Some(BatteryUpdate {
+                    event_type: event.event_type(),
+                    sequence_number: event.sequence_number(),
+                    battery,
+                });
+            }
+        }
+
+        None
+    }
+}
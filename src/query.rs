@@ -0,0 +1,452 @@
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use std::os::unix::prelude::*;
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::Enumerator;
+use ::monitor::Monitor;
+
+
+/// A reusable description of a set of devices.
+///
+/// A `DeviceQuery` collects the same filters as an `Enumerator`, but it's a plain value: it can be
+/// built without a context, cloned, compared, and printed. Filters are only validated when the
+/// query is executed, so building a query never fails.
+///
+/// The filters combine the same way as in libudev:
+///
+/// * A device matches if it belongs to any of the subsystems, has any of the sysnames, and has any
+///   of the property values.
+/// * A device must have all of the attribute values and all of the tags.
+/// * A device must not belong to any of the excluded subsystems, have any of the excluded
+///   sysnames, or have any of the excluded attribute values.
+/// * A device must be the parent or a descendant of it. Like in libudev, a query has at most one
+///   parent, and setting another parent replaces it.
+/// * Devices added by syspath are included whether or not they match the other filters.
+///
+/// Subsystems, sysnames, property names, property values, and attribute values may be glob
+/// patterns.
+///
+/// ## Example
+///
+/// ```no_run
+/// use libudev::DeviceQuery;
+///
+/// let query = DeviceQuery::new()
+///     .subsystem("tty")
+///     .property("ID_BUS", "usb");
+///
+/// let context = libudev::Context::new().unwrap();
+///
+/// for device in query.execute(&context).unwrap() {
+///     println!("{:?}", device.devnode());
+/// }
+/// ```
+#[derive(Debug,Clone,Default,PartialEq,Eq,Hash)]
+pub struct DeviceQuery {
+    pub(crate) subsystems: Vec<OsString>,
+    pub(crate) nomatch_subsystems: Vec<OsString>,
+    pub(crate) sysnames: Vec<OsString>,
+    pub(crate) nomatch_sysnames: Vec<OsString>,
+    pub(crate) properties: Vec<(OsString, OsString)>,
+    pub(crate) attributes: Vec<(OsString, OsString)>,
+    pub(crate) nomatch_attributes: Vec<(OsString, OsString)>,
+    pub(crate) tags: Vec<OsString>,
+    pub(crate) parent: Option<PathBuf>,
+    pub(crate) syspaths: Vec<PathBuf>,
+    pub(crate) initialized: bool,
+    pub(crate) initialized_for: Option<Duration>,
+    pub(crate) initialized_within: Option<Duration>,
+}
+
+impl DeviceQuery {
+    /// Creates a query that matches all devices.
+    pub fn new() -> Self {
+        DeviceQuery::default()
+    }
+
+    /// Adds a filter that matches devices that belong to the given kernel subsystem.
+    pub fn subsystem<T: AsRef<OsStr>>(mut self, subsystem: T) -> Self {
+        self.subsystems.push(subsystem.as_ref().to_os_string());
+        self
+    }
+
+    /// Adds a filter that matches devices that don't belong to the given kernel subsystem.
+    pub fn nomatch_subsystem<T: AsRef<OsStr>>(mut self, subsystem: T) -> Self {
+        self.nomatch_subsystems.push(subsystem.as_ref().to_os_string());
+        self
+    }
+
+    /// Adds a filter that matches devices with the given kernel device name.
+    pub fn sysname<T: AsRef<OsStr>>(mut self, sysname: T) -> Self {
+        self.sysnames.push(sysname.as_ref().to_os_string());
+        self
+    }
+
+    /// Adds a filter that matches devices that don't have the given kernel device name.
+    pub fn nomatch_sysname<T: AsRef<OsStr>>(mut self, sysname: T) -> Self {
+        self.nomatch_sysnames.push(sysname.as_ref().to_os_string());
+        self
+    }
+
+    /// Adds a filter that matches devices with the given property value.
+    pub fn property<T: AsRef<OsStr>, U: AsRef<OsStr>>(mut self, property: T, value: U) -> Self {
+        self.properties.push((property.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self
+    }
+
+    /// Adds a filter that matches devices with the given attribute value.
+    pub fn attribute<T: AsRef<OsStr>, U: AsRef<OsStr>>(mut self, attribute: T, value: U) -> Self {
+        self.attributes.push((attribute.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self
+    }
+
+    /// Adds a filter that matches devices that don't have the given attribute value.
+    pub fn nomatch_attribute<T: AsRef<OsStr>, U: AsRef<OsStr>>(mut self, attribute: T, value: U) -> Self {
+        self.nomatch_attributes.push((attribute.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self
+    }
+
+    /// Adds a filter that matches devices with the given tag.
+    pub fn tag<T: AsRef<OsStr>>(mut self, tag: T) -> Self {
+        self.tags.push(tag.as_ref().to_os_string());
+        self
+    }
+
+    /// Adds a filter that matches the device with the given syspath and all devices in its subtree.
+    ///
+    /// A query has only one parent, so this replaces any parent that was set before.
+    pub fn parent<T: AsRef<Path>>(mut self, syspath: T) -> Self {
+        self.parent = Some(syspath.as_ref().to_path_buf());
+        self
+    }
+
+    /// Includes the device with the given syspath.
+    pub fn syspath<T: AsRef<Path>>(mut self, syspath: T) -> Self {
+        self.syspaths.push(syspath.as_ref().to_path_buf());
+        self
+    }
+
+    /// Adds a filter that matches only devices that have been initialized by udev.
+    pub fn initialized(mut self) -> Self {
+        self.initialized = true;
+        self
+    }
+
+    /// Adds a filter that matches only devices that were initialized by udev at least `duration`
+    /// ago.
+    pub fn initialized_for(mut self, duration: Duration) -> Self {
+        self.initialized_for = Some(duration);
+        self
+    }
+
+    /// Adds a filter that matches only devices that were initialized by udev at most `duration`
+    /// ago.
+    pub fn initialized_within(mut self, duration: Duration) -> Self {
+        self.initialized_within = Some(duration);
+        self
+    }
+
+    /// Returns the subsystem patterns that devices may match.
+    pub fn subsystems(&self) -> &[OsString] {
+        &self.subsystems
+    }
+
+    /// Returns the subsystem patterns that devices must not match.
+    pub fn nomatch_subsystems(&self) -> &[OsString] {
+        &self.nomatch_subsystems
+    }
+
+    /// Returns the sysname patterns that devices may match.
+    pub fn sysnames(&self) -> &[OsString] {
+        &self.sysnames
+    }
+
+    /// Returns the sysname patterns that devices must not match.
+    pub fn nomatch_sysnames(&self) -> &[OsString] {
+        &self.nomatch_sysnames
+    }
+
+    /// Returns the `(property pattern, value pattern)` pairs of which devices must match at least
+    /// one.
+    pub fn properties(&self) -> &[(OsString, OsString)] {
+        &self.properties
+    }
+
+    /// Returns the `(attribute, pattern)` pairs that devices must all match.
+    pub fn attributes(&self) -> &[(OsString, OsString)] {
+        &self.attributes
+    }
+
+    /// Returns the `(attribute, pattern)` pairs that devices must not match.
+    pub fn nomatch_attributes(&self) -> &[(OsString, OsString)] {
+        &self.nomatch_attributes
+    }
+
+    /// Returns the tags that devices must all have.
+    pub fn tags(&self) -> &[OsString] {
+        &self.tags
+    }
+
+    /// Returns the syspath of the device whose subtree devices must belong to.
+    pub fn parent_syspath(&self) -> Option<&Path> {
+        self.parent.as_deref()
+    }
+
+    /// Returns the syspaths of devices that are included regardless of the other filters.
+    pub fn syspaths(&self) -> &[PathBuf] {
+        &self.syspaths
+    }
+
+    /// Returns `true` if only initialized devices match, as set by `initialized()`.
+    pub fn requires_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// Returns the minimum time since a device's initialization, as set by `initialized_for()`.
+    pub fn requires_initialized_for(&self) -> Option<Duration> {
+        self.initialized_for
+    }
+
+    /// Returns the maximum time since a device's initialization, as set by
+    /// `initialized_within()`.
+    pub fn requires_initialized_within(&self) -> Option<Duration> {
+        self.initialized_within
+    }
+
+    /// Creates an `Enumerator` with the query's filters.
+    ///
+    /// Any error from adding a filter to the enumerator is returned, including an error for a
+    /// parent that doesn't exist.
+    pub fn enumerator(&self, context: &Context) -> ::Result<Enumerator> {
+        let mut enumerator = Enumerator::new(context)?;
+
+        for subsystem in &self.subsystems {
+            enumerator.match_subsystem(subsystem)?;
+        }
+
+        for subsystem in &self.nomatch_subsystems {
+            enumerator.nomatch_subsystem(subsystem)?;
+        }
+
+        for sysname in &self.sysnames {
+            enumerator.match_sysname(sysname)?;
+        }
+
+        for sysname in &self.nomatch_sysnames {
+            enumerator.nomatch_sysname(sysname)?;
+        }
+
+        for (property, value) in &self.properties {
+            enumerator.match_property(property, value)?;
+        }
+
+        for (attribute, value) in &self.attributes {
+            enumerator.match_attribute(attribute, value)?;
+        }
+
+        for (attribute, value) in &self.nomatch_attributes {
+            enumerator.nomatch_attribute(attribute, value)?;
+        }
+
+        for tag in &self.tags {
+            enumerator.match_tag(tag)?;
+        }
+
+        if let Some(ref parent) = self.parent {
+            let parent = Device::from_syspath(context, parent)?;
+            enumerator.match_parent(&parent)?;
+        }
+
+        for syspath in &self.syspaths {
+            enumerator.add_syspath(syspath)?;
+        }
+
+        if self.initialized {
+            enumerator.match_is_initialized()?;
+        }
+
+        if let Some(duration) = self.initialized_for {
+            enumerator.match_initialized_for(duration)?;
+        }
+
+        if let Some(duration) = self.initialized_within {
+            enumerator.match_initialized_within(duration)?;
+        }
+
+        Ok(enumerator)
+    }
+
+    /// Scans `/sys` for devices matching the query.
+    pub fn execute(&self, context: &Context) -> ::Result<Vec<Device>> {
+        let mut enumerator = self.enumerator(context)?;
+        let devices = enumerator.scan_devices()?.collect();
+
+        Ok(devices)
+    }
+
+    /// Adds the query's subsystem and tag filters to a monitor.
+    ///
+    /// Monitors can only filter events by subsystem and tag. The other filters in the query are
+    /// not applied, so events should be checked with `matches()` if the query contains them.
+    ///
+    /// Monitors compare subsystems and tags exactly, so an `InvalidInput` error is returned if any
+    /// of them is a glob pattern. In that case, no filters are added to the monitor.
+    pub fn apply_to_monitor(&self, monitor: &mut Monitor) -> ::Result<()> {
+        if self.subsystems.iter().chain(&self.tags).any(|s| is_glob(s)) {
+            return Err(::error::from_errno(-::libc::EINVAL));
+        }
+
+        for subsystem in &self.subsystems {
+            monitor.match_subsystem(subsystem)?;
+        }
+
+        for tag in &self.tags {
+            monitor.match_tag(tag)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether a device matches all of the query's filters.
+    ///
+    /// The filters are evaluated by this library rather than by libudev, but follow the same
+    /// rules as `execute()`, including glob patterns.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use libudev::DeviceQuery;
+    ///
+    /// let context = libudev::Context::new().unwrap();
+    /// let query = DeviceQuery::new().subsystem("tt*").property("DEV*", "/dev/tty*");
+    ///
+    /// let all = DeviceQuery::new().execute(&context).unwrap();
+    /// let matched = query.execute(&context).unwrap();
+    ///
+    /// let expected: Vec<_> = matched.iter().map(|device| device.syspath()).collect();
+    /// let actual: Vec<_> = all.iter().filter(|device| query.matches(device)).map(|device| device.syspath()).collect();
+    ///
+    /// assert_eq!(actual, expected);
+    /// ```
+    pub fn matches(&self, device: &Device) -> bool {
+        if self.syspaths.iter().any(|p| device.syspath() == Some(p.as_path())) {
+            return true;
+        }
+
+        if !self.subsystems.is_empty() && !self.subsystems.iter().any(|s| device.subsystem().is_some_and(|d| ::util::fnmatch(s, d))) {
+            return false;
+        }
+
+        if self.nomatch_subsystems.iter().any(|s| device.subsystem().is_some_and(|d| ::util::fnmatch(s, d))) {
+            return false;
+        }
+
+        if !self.sysnames.is_empty() && !self.sysnames.iter().any(|s| device.sysname().is_some_and(|n| ::util::fnmatch(s, n))) {
+            return false;
+        }
+
+        if !self.matches_nomatch_sysnames(device.sysname()) {
+            return false;
+        }
+
+        if !self.properties.is_empty() && !self.properties.iter().any(|(p, v)| device.properties().any(|d| ::util::fnmatch(p, d.name()) && ::util::fnmatch(v, d.value()))) {
+            return false;
+        }
+
+        if !self.attributes.iter().all(|(a, v)| device.attribute_value(a).is_some_and(|d| ::util::fnmatch(v, d))) {
+            return false;
+        }
+
+        if self.nomatch_attributes.iter().any(|(a, v)| device.attribute_value(a).is_some_and(|d| ::util::fnmatch(v, d))) {
+            return false;
+        }
+
+        if !self.tags.iter().all(|t| device.has_tag(t)) {
+            return false;
+        }
+
+        if let Some(ref parent) = self.parent {
+            if !device.syspath().is_some_and(|s| s.starts_with(parent)) {
+                return false;
+            }
+        }
+
+        // libudev always includes devices without a device node or network interface, since udev
+        // doesn't record their initialization.
+        if self.initialized && !device.is_initialized() && (device.devnode().is_some() || device.property_value("IFINDEX").is_some()) {
+            return false;
+        }
+
+        self.matches_initialization_time(device)
+    }
+
+    /// Checks the client-side filters on a device's initialization time.
+    pub(crate) fn matches_initialization_time(&self, device: &Device) -> bool {
+        if self.initialized_for.is_none() && self.initialized_within.is_none() {
+            return true;
+        }
+
+        let elapsed = match device.usec_since_initialized() {
+            Some(elapsed) => elapsed,
+            None => return false,
+        };
+
+        if let Some(min) = self.initialized_for {
+            if elapsed < min {
+                return false;
+            }
+        }
+
+        if let Some(max) = self.initialized_within {
+            if elapsed > max {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Checks whether a device class below `/sys/class` matches the query.
+    ///
+    /// Classes are matched like the buses found by `Enumerator::scan_subsystems()`: the subsystem
+    /// and sysname filters match the name of the class. Classes have no properties, attributes,
+    /// tags, or parents, so they don't match queries with such filters.
+    pub(crate) fn matches_class(&self, name: &OsStr) -> bool {
+        if !self.sysnames.is_empty() && !self.sysnames.iter().any(|s| ::util::fnmatch(s, name)) {
+            return false;
+        }
+
+        self.matches_subsystem_name(name)
+            && self.properties.is_empty()
+            && self.attributes.is_empty()
+            && self.tags.is_empty()
+            && self.parent.is_none()
+    }
+
+    /// Checks the subsystem filters and the excluded sysnames against the name of a bus or class.
+    pub(crate) fn matches_subsystem_name(&self, name: &OsStr) -> bool {
+        if !self.subsystems.is_empty() && !self.subsystems.iter().any(|s| ::util::fnmatch(s, name)) {
+            return false;
+        }
+
+        if self.nomatch_subsystems.iter().any(|s| ::util::fnmatch(s, name)) {
+            return false;
+        }
+
+        self.matches_nomatch_sysnames(Some(name))
+    }
+
+    /// Checks the client-side filter on excluded sysnames.
+    pub(crate) fn matches_nomatch_sysnames(&self, sysname: Option<&OsStr>) -> bool {
+        !self.nomatch_sysnames.iter().any(|s| sysname.is_some_and(|n| ::util::fnmatch(s, n)))
+    }
+}
+
+/// Checks whether a filter value contains glob pattern characters.
+fn is_glob(value: &OsStr) -> bool {
+    value.as_bytes().iter().any(|b| matches!(*b, b'*' | b'?' | b'['))
+}
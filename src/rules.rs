@@ -0,0 +1,783 @@
+//! Offline evaluation of udev rules.
+//!
+//! This module parses udev rules files and evaluates them against a device, reporting the
+//! assignments that udev would make: the device node's name, mode, owner, and group, symlinks,
+//! properties, tags, attribute writes, and programs to run. Nothing is changed on the system, and
+//! `RUN` and `PROGRAM` commands are recorded instead of being executed.
+//!
+//! Because rules can be evaluated against a `DeviceSnapshot`, rules files can be tested with
+//! fixtures, including parent devices for keys such as `ATTRS` and `KERNELS`.
+//!
+//! ## Example
+//!
+//! ```
+//! use libudev::{Action, DeviceSnapshot};
+//! use libudev::rules::Rules;
+//!
+//! let rules: Rules = r#"
+//! ## FTDI serial adapters
+//! SUBSYSTEM=="tty", ATTRS{idVendor}=="0403", \
+//!     SYMLINK+="ftdi-%n", MODE="0660", GROUP="dialout", TAG+="uaccess"
+//! "#.parse().unwrap();
+//!
+//! let mut usb = DeviceSnapshot::new();
+//! usb.set_attribute("idVendor", "0403");
+//!
+//! let mut tty = DeviceSnapshot::new();
+//! tty.set_property("DEVPATH", "/devices/pci0000:00/0000:00:14.0/usb1/1-1/1-1:1.0/ttyUSB0/tty/ttyUSB0").unwrap();
+//! tty.set_property("SUBSYSTEM", "tty").unwrap();
+//! tty.set_parent(usb);
+//!
+//! let result = rules.evaluate(&tty, Action::Add);
+//!
+//! assert_eq!(result.symlinks(), ["ftdi-0"]);
+//! assert_eq!(result.mode(), Some("0660"));
+//! assert_eq!(result.group(), Some("dialout"));
+//! assert_eq!(result.tags(), ["uaccess"]);
+//! ```
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ::filter::{self, Filter};
+use ::filter::syntax::{self, Operator, Pair};
+use ::info::DeviceInfo;
+use ::trigger::Action;
+
+
+/// An error that occurred while parsing a rules file.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ParseError {
+    path: Option<PathBuf>,
+    line: usize,
+    error: filter::ParseError,
+}
+
+impl ParseError {
+    /// Returns the path of the rules file, if the rules were read from a file.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns the line on which the rule with the error starts, counting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the syntax error. Its offset is relative to the start of the rule, with any
+    /// continuation lines joined.
+    pub fn syntax_error(&self) -> &filter::ParseError {
+        &self.error
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.path {
+            Some(ref path) => write!(f, "{}:{}: {}", path.display(), self.line, self.error),
+            None => write!(f, "line {}: {}", self.line, self.error),
+        }
+    }
+}
+
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<ParseError> for io::Error {
+    fn from(error: ParseError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+
+/// A parsed set of udev rules.
+///
+/// Rules are evaluated in the order in which they were added, so files should be added in the
+/// order that udev reads them: sorted by file name across all rules directories.
+///
+/// Match keys that can't be evaluated offline, such as `RESULT`, `TEST`, and `SYSCTL`, never
+/// match. `PROGRAM` is assumed to succeed, and is recorded if the rest of the rule matches.
+/// Assignments to keys other than the ones reported by `Evaluation` are ignored.
+///
+/// Like in udev, `$attr{}` substitutions read the attribute from the parent that was matched by
+/// the rule's `KERNELS`, `SUBSYSTEMS`, `DRIVERS`, or `ATTRS` keys, and from the device itself if
+/// the parent doesn't have the attribute.
+///
+/// ## Example
+///
+/// ```
+/// use libudev::{Action, DeviceSnapshot};
+/// use libudev::rules::Rules;
+///
+/// let rules: Rules = r#"
+/// SUBSYSTEM=="tty", ATTRS{idVendor}=="0403", SYMLINK+="x-$attr{serial}"
+/// PROGRAM="/usr/bin/check %k", NAME=="never"
+/// "#.parse().unwrap();
+///
+/// let mut usb = DeviceSnapshot::new();
+/// usb.set_attribute("idVendor", "0403");
+/// usb.set_attribute("serial", "A50285BI");
+///
+/// let mut interface = DeviceSnapshot::new();
+/// interface.set_attribute("serial", "interface");
+/// interface.set_parent(usb);
+///
+/// let mut tty = DeviceSnapshot::new();
+/// tty.set_property("DEVPATH", "/devices/virtual/tty/ttyUSB0").unwrap();
+/// tty.set_property("SUBSYSTEM", "tty").unwrap();
+/// tty.set_parent(interface);
+///
+/// let result = rules.evaluate(&tty, Action::Add);
+///
+/// assert_eq!(result.symlinks(), ["x-A50285BI"]);
+/// assert!(result.programs().is_empty());
+/// ```
+#[derive(Debug,Clone,Default)]
+pub struct Rules {
+    files: Vec<Option<PathBuf>>,
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    /// Creates an empty rule set.
+    pub fn new() -> Self {
+        Rules::default()
+    }
+
+    /// Parses rules from a string.
+    pub fn parse(text: &str) -> Result<Rules, ParseError> {
+        let mut rules = Rules::new();
+        rules.add_rules(text)?;
+        Ok(rules)
+    }
+
+    /// Adds the rules contained in a string.
+    pub fn add_rules(&mut self, text: &str) -> Result<(), ParseError> {
+        self.add(None, text)
+    }
+
+    /// Adds the rules contained in a file.
+    ///
+    /// Syntax errors are reported as an `InvalidData` error that wraps a `ParseError`.
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+
+        self.add(Some(path.to_path_buf()), &text)?;
+
+        Ok(())
+    }
+
+    /// Returns the number of rules.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns `true` if there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Evaluates the rules for an event with the given action.
+    ///
+    /// The action is visible to `ACTION` match keys and as the `ACTION` property, including in
+    /// `$env{ACTION}` substitutions. Properties and tags assigned by earlier rules are visible to
+    /// the match keys of later rules.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use libudev::{Action, DeviceSnapshot};
+    /// use libudev::rules::Rules;
+    ///
+    /// let rules: Rules = r#"ACTION=="add|change", ENV{LAST_EVENT}="$env{ACTION}", RUN+="/usr/bin/notify %E{ACTION}""#.parse().unwrap();
+    ///
+    /// let result = rules.evaluate(&DeviceSnapshot::new(), Action::Change);
+    ///
+    /// assert_eq!(result.property_value("LAST_EVENT"), Some("change"));
+    /// assert_eq!(result.run(), ["/usr/bin/notify change"]);
+    /// ```
+    pub fn evaluate<D: DeviceInfo>(&self, device: &D, action: Action) -> Evaluation {
+        let mut evaluation = Evaluation::default();
+        let mut index = 0;
+
+        while let Some(rule) = self.rules.get(index) {
+            index += 1;
+
+            if !rule.matches(device, action, &mut evaluation) {
+                continue;
+            }
+
+            for assignment in &rule.assignments {
+                let value = substitute(&assignment.value, device, action, &evaluation);
+                evaluation.apply(assignment, value, device);
+            }
+
+            if let Some(ref label) = rule.goto {
+                let target = self.rules[index..].iter()
+                    .position(|r| r.file == rule.file && r.label.as_ref() == Some(label));
+
+                if let Some(offset) = target {
+                    index += offset;
+                }
+            }
+        }
+
+        evaluation
+    }
+
+    fn add(&mut self, path: Option<PathBuf>, text: &str) -> Result<(), ParseError> {
+        let file = self.files.len();
+        let mut rules = Vec::new();
+
+        for (line, rule) in logical_lines(text) {
+            let parsed = Rule::parse(file, &rule).map_err(|error| {
+                ParseError {
+                    path: path.clone(),
+                    line,
+                    error,
+                }
+            });
+
+            rules.push(parsed?);
+        }
+
+        self.files.push(path);
+        self.rules.extend(rules);
+
+        Ok(())
+    }
+}
+
+/// Parses rules with `Rules::parse()`.
+impl FromStr for Rules {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Rules, ParseError> {
+        Rules::parse(s)
+    }
+}
+
+
+#[derive(Debug,Clone)]
+struct Rule {
+    file: usize,
+    filter: Filter,
+    ancestor_filter: Option<Filter>,
+    conditions: Vec<Condition>,
+    assignments: Vec<Pair>,
+    label: Option<String>,
+    goto: Option<String>,
+}
+
+#[derive(Debug,Clone)]
+enum Condition {
+    Name(Operator, String),
+    Symlink(Operator, String),
+    Program(String),
+    Unsupported,
+}
+
+impl Rule {
+    fn parse(file: usize, text: &str) -> Result<Rule, filter::ParseError> {
+        let mut filter_pairs = Vec::new();
+        let mut conditions = Vec::new();
+        let mut assignments = Vec::new();
+        let mut label = None;
+        let mut goto = None;
+
+        for pair in syntax::tokenize(text)? {
+            match pair.key.as_str() {
+                "LABEL" => label = Some(pair.value),
+                "GOTO" => goto = Some(pair.value),
+                "PROGRAM" => conditions.push(Condition::Program(pair.value)),
+                "NAME" if pair.operator.is_match() => conditions.push(Condition::Name(pair.operator, pair.value)),
+                "SYMLINK" if pair.operator.is_match() => conditions.push(Condition::Symlink(pair.operator, pair.value)),
+                key if pair.operator.is_match() && syntax::is_filter_key(key) => filter_pairs.push(pair),
+                _ if pair.operator.is_match() => conditions.push(Condition::Unsupported),
+                _ => assignments.push(pair),
+            }
+        }
+
+        let (filter, ancestor_filter) = syntax::compile_split(&filter_pairs)?;
+
+        Ok(Rule {
+            file,
+            filter,
+            ancestor_filter,
+            conditions,
+            assignments,
+            label,
+            goto,
+        })
+    }
+
+    fn matches(&self, device: &dyn DeviceInfo, action: Action, evaluation: &mut Evaluation) -> bool {
+        evaluation.parent_depth = None;
+
+        let matched = {
+            let overlay = Overlay {
+                device,
+                action: OsStr::new(action.as_str()),
+                evaluation,
+            };
+
+            match (self.filter.matches(&overlay), &self.ancestor_filter) {
+                (false, _) => None,
+                (true, None) => Some(None),
+                (true, Some(filter)) => find_ancestor(&overlay, filter, 0).map(Some),
+            }
+        };
+
+        match matched {
+            Some(depth) => evaluation.parent_depth = depth,
+            None => return false,
+        }
+
+        let mut programs = Vec::new();
+
+        for condition in &self.conditions {
+            let matched = match *condition {
+                Condition::Name(operator, ref pattern) => {
+                    let name = evaluation.name.as_ref().map_or("", |name| name.as_str());
+                    (operator == Operator::Match) == glob_match(pattern, name)
+                },
+                Condition::Symlink(operator, ref pattern) => {
+                    (operator == Operator::Match) == evaluation.symlinks.iter().any(|link| glob_match(pattern, link))
+                },
+                Condition::Program(ref command) => {
+                    programs.push(substitute(command, device, action, evaluation));
+                    true
+                },
+                Condition::Unsupported => false,
+            };
+
+            if !matched {
+                return false;
+            }
+        }
+
+        evaluation.programs.extend(programs);
+
+        true
+    }
+}
+
+
+/// The assignments made by a set of rules for one device.
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct Evaluation {
+    name: Option<String>,
+    symlinks: Vec<String>,
+    properties: BTreeMap<String, String>,
+    tags: Vec<String>,
+    removed_tags: Vec<String>,
+    mode: Option<String>,
+    owner: Option<String>,
+    group: Option<String>,
+    attributes: Vec<(String, String)>,
+    run: Vec<String>,
+    programs: Vec<String>,
+    finals: HashSet<String>,
+    parent_depth: Option<usize>,
+}
+
+impl Evaluation {
+    /// Returns the name assigned with `NAME`.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the symlinks assigned with `SYMLINK`.
+    pub fn symlinks(&self) -> &[String] {
+        &self.symlinks
+    }
+
+    /// Returns the value assigned to a property with `ENV`. An empty value means the property
+    /// was removed.
+    pub fn property_value(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(|s| s.as_str())
+    }
+
+    /// Returns an iterator over the properties assigned with `ENV` as `(name, value)` pairs,
+    /// sorted by name.
+    pub fn properties(&self) -> AssignedProperties<'_> {
+        AssignedProperties {
+            inner: self.properties.iter(),
+        }
+    }
+
+    /// Returns the tags added with `TAG`.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Returns the tags removed with `TAG-=`.
+    pub fn removed_tags(&self) -> &[String] {
+        &self.removed_tags
+    }
+
+    /// Returns the device node's mode assigned with `MODE`.
+    pub fn mode(&self) -> Option<&str> {
+        self.mode.as_deref()
+    }
+
+    /// Returns the device node's owner assigned with `OWNER`.
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    /// Returns the device node's group assigned with `GROUP`.
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    /// Returns the attribute writes requested with `ATTR` as `(attribute, value)` pairs, in order.
+    pub fn attributes(&self) -> &[(String, String)] {
+        &self.attributes
+    }
+
+    /// Returns the commands that `RUN` would execute after the rules are processed.
+    pub fn run(&self) -> &[String] {
+        &self.run
+    }
+
+    /// Returns the `PROGRAM` commands of the matching rules, which were not run.
+    pub fn programs(&self) -> &[String] {
+        &self.programs
+    }
+
+    fn apply(&mut self, assignment: &Pair, value: String, device: &dyn DeviceInfo) {
+        let key = assignment.key.as_str();
+        let operator = assignment.operator;
+
+        match key {
+            "ENV" | "ATTR" => {},
+            _ if self.finals.contains(key) => return,
+            _ if operator == Operator::AssignFinal => {
+                self.finals.insert(key.to_owned());
+            },
+            _ => {},
+        }
+
+        match key {
+            "NAME" => self.name = Some(value),
+            "MODE" => self.mode = Some(value),
+            "OWNER" => self.owner = Some(value),
+            "GROUP" => self.group = Some(value),
+            "SYMLINK" => update_list(&mut self.symlinks, operator, value.split_whitespace()),
+            "RUN" => update_list(&mut self.run, operator, Some(value.as_str())),
+            "TAG" => {
+                if operator == Operator::Remove {
+                    self.removed_tags.push(value.clone());
+                }
+                else {
+                    self.removed_tags.retain(|tag| *tag != value);
+                }
+
+                update_list(&mut self.tags, operator, Some(value.as_str()));
+            },
+            "ENV" => {
+                if let Some(ref name) = assignment.attribute {
+                    let value = match operator {
+                        Operator::Remove => return,
+                        Operator::Add => {
+                            let current = self.properties.get(name).cloned()
+                                .or_else(|| lossy(device.property_value(OsStr::new(name))))
+                                .unwrap_or_default();
+
+                            if current.is_empty() {
+                                value
+                            }
+                            else {
+                                format!("{} {}", current, value)
+                            }
+                        },
+                        _ => value,
+                    };
+
+                    self.properties.insert(name.clone(), value);
+                }
+            },
+            "ATTR" => {
+                if let Some(ref name) = assignment.attribute {
+                    self.attributes.push((name.clone(), value));
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Iterator over the properties assigned by rules.
+pub struct AssignedProperties<'a> {
+    inner: ::std::collections::btree_map::Iter<'a, String, String>,
+}
+
+impl<'a> Iterator for AssignedProperties<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<(&'a str, &'a str)> {
+        self.inner.next().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+
+fn update_list<'a, I: IntoIterator<Item = &'a str>>(list: &mut Vec<String>, operator: Operator, values: I) {
+    if operator == Operator::Assign || operator == Operator::AssignFinal {
+        list.clear();
+    }
+
+    for value in values {
+        if operator == Operator::Remove {
+            list.retain(|v| v != value);
+        }
+        else if !list.iter().any(|v| v == value) {
+            list.push(value.to_owned());
+        }
+    }
+}
+
+
+/// A device with the properties and tags assigned by earlier rules applied on top.
+struct Overlay<'a> {
+    device: &'a dyn DeviceInfo,
+    action: &'a OsStr,
+    evaluation: &'a Evaluation,
+}
+
+impl<'a> DeviceInfo for Overlay<'a> {
+    fn devpath(&self) -> Option<&OsStr> {
+        self.device.devpath()
+    }
+
+    fn subsystem(&self) -> Option<&OsStr> {
+        self.device.subsystem()
+    }
+
+    fn sysname(&self) -> Option<&OsStr> {
+        self.device.sysname()
+    }
+
+    fn devtype(&self) -> Option<&OsStr> {
+        self.device.devtype()
+    }
+
+    fn driver(&self) -> Option<&OsStr> {
+        self.device.driver()
+    }
+
+    fn devnode(&self) -> Option<&Path> {
+        self.device.devnode()
+    }
+
+    fn property_value(&self, property: &OsStr) -> Option<&OsStr> {
+        if property == "ACTION" {
+            return Some(self.action);
+        }
+
+        match property.to_str().and_then(|name| self.evaluation.properties.get(name)) {
+            Some(value) if value.is_empty() => None,
+            Some(value) => Some(OsStr::new(value)),
+            None => self.device.property_value(property),
+        }
+    }
+
+    fn attribute_value(&self, attribute: &OsStr) -> Option<&OsStr> {
+        self.device.attribute_value(attribute)
+    }
+
+    fn has_tag(&self, tag: &OsStr) -> bool {
+        if self.evaluation.removed_tags.iter().any(|t| OsStr::new(t) == tag) {
+            return false;
+        }
+
+        self.evaluation.tags.iter().any(|t| OsStr::new(t) == tag) || self.device.has_tag(tag)
+    }
+
+    fn visit_tags(&self, visitor: &mut dyn FnMut(&OsStr) -> bool) -> bool {
+        let removed = &self.evaluation.removed_tags;
+        let mut visitor = |tag: &OsStr| !removed.iter().any(|t| OsStr::new(t) == tag) && visitor(tag);
+
+        self.evaluation.tags.iter().any(|t| visitor(OsStr::new(t))) || self.device.visit_tags(&mut visitor)
+    }
+
+    fn visit_parent(&self, visitor: &mut dyn FnMut(&dyn DeviceInfo) -> bool) -> bool {
+        self.device.visit_parent(visitor)
+    }
+}
+
+
+/// Splits rules text into rules, joining continuation lines and skipping comments and blank
+/// lines. Returns each rule with the number of the line on which it starts.
+fn logical_lines(text: &str) -> Vec<(usize, String)> {
+    let mut rules = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let (start, mut rule) = match current.take() {
+            Some(rule) => rule,
+            None => {
+                let trimmed = line.trim_start();
+
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    continue;
+                }
+
+                (index + 1, String::new())
+            },
+        };
+
+        if let Some(line) = line.strip_suffix('\\') {
+            rule.push_str(line);
+            current = Some((start, rule));
+        }
+        else {
+            rule.push_str(line);
+            rules.push((start, rule));
+        }
+    }
+
+    rules.extend(current);
+    rules
+}
+
+/// Returns the number of parents between the device and the first device in its chain that matches
+/// `filter`.
+fn find_ancestor(device: &dyn DeviceInfo, filter: &Filter, depth: usize) -> Option<usize> {
+    if filter.evaluate(device) {
+        return Some(depth);
+    }
+
+    let mut found = None;
+    device.visit_parent(&mut |parent| {
+        found = find_ancestor(parent, filter, depth + 1);
+        found.is_some()
+    });
+
+    found
+}
+
+/// Reads an attribute of the ancestor that is `depth` parents above the device.
+fn ancestor_attribute(device: &dyn DeviceInfo, depth: usize, attribute: &OsStr) -> Option<String> {
+    if depth == 0 {
+        return lossy(device.attribute_value(attribute));
+    }
+
+    let mut value = None;
+    device.visit_parent(&mut |parent| {
+        value = ancestor_attribute(parent, depth - 1, attribute);
+        true
+    });
+
+    value
+}
+
+/// Matches a value against a udev pattern: glob patterns separated by `|`.
+fn glob_match(patterns: &str, value: &str) -> bool {
+    patterns.split('|').any(|pattern| ::util::fnmatch(pattern, value))
+}
+
+/// Replaces udev's `$name` and `%c` substitutions in an assigned value.
+///
+/// The supported substitutions are `$kernel` (`%k`), `$number` (`%n`), `$devpath` (`%p`),
+/// `$driver` (`%d`), `$attr{name}` (`%s{name}`), `$env{name}` (`%E{name}`), `$major` (`%M`),
+/// `$minor` (`%m`), `$name` (`%D`), `$links`, `$devnode` (`%N`), `$root` (`%r`), `$sys` (`%S`),
+/// `$result` (`%c`), `$$`, and `%%`. Because programs aren't run, `$result` is always empty.
+/// Unknown substitutions are left unchanged.
+fn substitute(value: &str, device: &dyn DeviceInfo, action: Action, evaluation: &Evaluation) -> String {
+    let overlay = Overlay {
+        device,
+        action: OsStr::new(action.as_str()),
+        evaluation,
+    };
+
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find(&['$', '%'][..]) {
+        result.push_str(&rest[..start]);
+
+        let sigil = rest.as_bytes()[start];
+        let after = &rest[start + 1..];
+
+        let name_len = match sigil {
+            b'$' if after.starts_with('$') => 1,
+            b'$' => after.bytes().take_while(u8::is_ascii_lowercase).count(),
+            _ => after.chars().next().map_or(0, char::len_utf8),
+        };
+
+        let name = &after[..name_len];
+        let mut consumed = 1 + name_len;
+
+        let argument = if after[name_len..].starts_with('{') {
+            after[name_len..].find('}').map(|end| {
+                consumed += end + 1;
+                &after[name_len + 1..name_len + end]
+            })
+        }
+        else {
+            None
+        };
+
+        let replacement = match (sigil, name) {
+            (b'$', "$") | (b'%', "%") => Some(name.to_owned()),
+            (b'$', "kernel") | (b'%', "k") => lossy(overlay.sysname()),
+            (b'$', "number") | (b'%', "n") => lossy(overlay.sysname()).map(|sysname| {
+                let digits = sysname.bytes().rev().take_while(|b| b.is_ascii_digit()).count();
+                sysname[sysname.len() - digits..].to_owned()
+            }),
+            (b'$', "devpath") | (b'%', "p") => lossy(overlay.devpath()),
+            (b'$', "driver") | (b'%', "d") => lossy(overlay.driver()),
+            (b'$', "attr") | (b'%', "s") => argument.map(|name| {
+                let name = OsStr::new(name);
+
+                evaluation.parent_depth.and_then(|depth| ancestor_attribute(&overlay, depth, name))
+                    .or_else(|| lossy(overlay.attribute_value(name)))
+                    .unwrap_or_default()
+            }),
+            (b'$', "env") | (b'%', "E") => argument.map(|name| lossy(overlay.property_value(OsStr::new(name))).unwrap_or_default()),
+            (b'$', "major") | (b'%', "M") => lossy(overlay.property_value(OsStr::new("MAJOR"))),
+            (b'$', "minor") | (b'%', "m") => lossy(overlay.property_value(OsStr::new("MINOR"))),
+            (b'$', "name") | (b'%', "D") => evaluation.name.clone().or_else(|| lossy(overlay.sysname())),
+            (b'$', "links") => Some(evaluation.symlinks.join(" ")),
+            (b'$', "devnode") | (b'%', "N") => lossy(overlay.devnode().map(|path| path.as_os_str())),
+            (b'$', "root") | (b'%', "r") => Some("/dev".to_owned()),
+            (b'$', "sys") | (b'%', "S") => Some("/sys".to_owned()),
+            (b'$', "result") | (b'%', "c") => Some(String::new()),
+            _ => None,
+        };
+
+        match replacement {
+            Some(replacement) => {
+                result.push_str(&replacement);
+                rest = &rest[start + consumed..];
+            },
+            None => {
+                result.push(sigil as char);
+                rest = after;
+            },
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn lossy(value: Option<&OsStr>) -> Option<String> {
+    value.map(|value| value.to_string_lossy().into_owned())
+}
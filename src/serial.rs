@@ -0,0 +1,267 @@
+//! Discovery of serial ports.
+//!
+//! Serial ports are devices in the `tty` subsystem, but most TTYs aren't serial ports: virtual
+//! consoles, pseudo-terminals, and `/dev/tty` itself have no backing hardware. The kernel also
+//! registers a fixed number of legacy `ttyS*` ports whether or not a UART exists. `ports()` skips
+//! all of these and describes the remaining ports with `SerialPortInfo`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! let context = libudev::Context::new().unwrap();
+//!
+//! for port in libudev::serial::ports(&context).unwrap() {
+//!     print!("{} ({:?})", port.devnode().display(), port.bus());
+//!
+//!     if let (Some(vendor_id), Some(product_id)) = (port.vendor_id(), port.product_id()) {
+//!         print!(" {:04x}:{:04x} interface {:?}", vendor_id, product_id, port.interface_number());
+//!     }
+//!
+//!     println!();
+//! }
+//! ```
+
+use std::fmt;
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::{Enumerator, SortOrder};
+use ::usb::{UsbDevice, UsbInterface};
+
+
+/// Returns the serial ports in the system, ordered by sysname.
+pub fn ports(context: &Context) -> ::Result<Vec<SerialPortInfo>> {
+    let mut enumerator = Enumerator::new(context)?;
+
+    enumerator.match_subsystem("tty")?;
+
+    let devices = enumerator.scan_devices()?.sorted(SortOrder::Sysnum);
+
+    Ok(devices.into_iter().filter_map(SerialPortInfo::from_device).collect())
+}
+
+
+/// The kind of bus that a serial port is attached to.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Bus {
+    /// A USB serial adapter or a USB modem.
+    Usb,
+
+    /// A serial port on a PCI card.
+    Pci,
+
+    /// A UART described by the platform, e.g., by a device tree.
+    Platform,
+
+    /// A UART described by ACPI or PnP BIOS, such as the legacy COM ports of a PC.
+    Pnp,
+
+    /// Any other bus.
+    Other,
+}
+
+
+/// A serial port and the hardware behind it.
+#[derive(Clone)]
+pub struct SerialPortInfo {
+    device: Device,
+    devnode: PathBuf,
+    hardware: Option<Device>,
+    bus: Bus,
+    usb_device: Option<UsbDevice>,
+    usb_interface: Option<UsbInterface>,
+}
+
+impl SerialPortInfo {
+    /// Describes the serial port of a TTY device.
+    ///
+    /// Returns `None` if `device` isn't a TTY, has no device node, or has no backing hardware.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if device.subsystem() != Some(OsStr::new("tty")) {
+            return None;
+        }
+
+        let devnode = device.devnode()?.to_path_buf();
+
+        // Virtual consoles and pseudo-terminals don't have a `device` link.
+        if !device.syspath()?.join("device").exists() {
+            return None;
+        }
+
+        // The 8250 driver registers its legacy ports even if no UART responds. Their type is
+        // PORT_UNKNOWN.
+        if ::util::value_to_str(device.attribute_value("type")) == Some("0") {
+            return None;
+        }
+
+        let hardware = hardware(&device);
+
+        let bus = hardware.as_ref().and_then(find_bus).unwrap_or(Bus::Other);
+
+        let usb_interface = UsbInterface::find(&device);
+        let usb_device = UsbDevice::find(&device);
+
+        Some(SerialPortInfo {
+            device,
+            devnode,
+            hardware,
+            bus,
+            usb_device,
+            usb_interface,
+        })
+    }
+
+    /// Returns the TTY device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the device node of the port, e.g., `/dev/ttyUSB0`.
+    pub fn devnode(&self) -> &Path {
+        &self.devnode
+    }
+
+    /// Returns the stable symlinks that udev created below `/dev/serial/by-id`.
+    ///
+    /// These links are named after the USB identity of the device and stay the same wherever it's
+    /// plugged in.
+    pub fn by_id_links(&self) -> Vec<&Path> {
+        self.device.devlinks().filter(|link| link.starts_with("/dev/serial/by-id")).collect()
+    }
+
+    /// Returns the stable symlinks that udev created below `/dev/serial/by-path`.
+    ///
+    /// These links are named after the physical port the device is connected to.
+    pub fn by_path_links(&self) -> Vec<&Path> {
+        self.device.devlinks().filter(|link| link.starts_with("/dev/serial/by-path")).collect()
+    }
+
+    /// Returns the device that implements the port, such as a USB interface, a PCI card, or a
+    /// platform UART.
+    pub fn hardware(&self) -> Option<&Device> {
+        self.hardware.as_ref()
+    }
+
+    /// Returns the name of the driver of the device that implements the port, e.g., `ftdi_sio`,
+    /// `cdc_acm`, or `serial`.
+    pub fn driver(&self) -> Option<&OsStr> {
+        self.hardware.as_ref().and_then(|hardware| hardware.driver())
+    }
+
+    /// Returns the kind of bus that the port is attached to.
+    pub fn bus(&self) -> Bus {
+        self.bus
+    }
+
+    /// Returns the USB device that provides the port, if it's attached to USB.
+    pub fn usb_device(&self) -> Option<&UsbDevice> {
+        self.usb_device.as_ref()
+    }
+
+    /// Returns the USB interface that provides the port, if it's attached to USB.
+    pub fn usb_interface(&self) -> Option<&UsbInterface> {
+        self.usb_interface.as_ref()
+    }
+
+    /// Returns the PCI device that provides the port, or the PCI host controller of its USB
+    /// device.
+    pub fn pci_device(&self) -> Option<Device> {
+        self.device.parent_with_subsystem("pci")
+    }
+
+    /// Returns the platform device that provides the port.
+    pub fn platform_device(&self) -> Option<Device> {
+        self.device.parent_with_subsystem("platform")
+    }
+
+    /// Returns the USB vendor ID of the port.
+    pub fn vendor_id(&self) -> Option<u16> {
+        self.usb_device.as_ref().and_then(|usb| usb.vendor_id())
+    }
+
+    /// Returns the USB product ID of the port.
+    pub fn product_id(&self) -> Option<u16> {
+        self.usb_device.as_ref().and_then(|usb| usb.product_id())
+    }
+
+    /// Returns the USB serial number of the port.
+    pub fn serial_number(&self) -> Option<&str> {
+        self.usb_device.as_ref().and_then(|usb| usb.serial())
+    }
+
+    /// Returns the USB manufacturer string of the port.
+    pub fn manufacturer(&self) -> Option<&str> {
+        self.usb_device.as_ref().and_then(|usb| usb.manufacturer())
+    }
+
+    /// Returns the USB product string of the port.
+    pub fn product(&self) -> Option<&str> {
+        self.usb_device.as_ref().and_then(|usb| usb.product())
+    }
+
+    /// Returns the number of the USB interface that provides the port.
+    ///
+    /// Composite devices, such as adapters with several ports or modems, provide one TTY per
+    /// interface.
+    pub fn interface_number(&self) -> Option<u8> {
+        self.usb_interface.as_ref().and_then(|interface| interface.interface_number())
+    }
+}
+
+impl fmt::Debug for SerialPortInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SerialPortInfo")
+            .field("devnode", &self.devnode)
+            .field("bus", &self.bus)
+            .field("driver", &self.driver())
+            .field("vendor_id", &self.vendor_id())
+            .field("product_id", &self.product_id())
+            .field("interface_number", &self.interface_number())
+            .finish()
+    }
+}
+
+
+/// Returns the closest ancestor of a TTY that is bound to a driver.
+///
+/// Since Linux 6.5, 8250 ports have `serial-base` controller and port devices between the TTY and
+/// the UART. Those are skipped.
+fn hardware(tty: &Device) -> Option<Device> {
+    let mut parent = tty.parent();
+
+    while let Some(device) = parent {
+        if device.driver().is_some() && device.subsystem() != Some(OsStr::new("serial-base")) {
+            return Some(device);
+        }
+
+        parent = device.parent();
+    }
+
+    None
+}
+
+/// Returns the bus of the closest ancestor that belongs to a bus this module knows about.
+fn find_bus(hardware: &Device) -> Option<Bus> {
+    let mut device = Some(hardware.clone());
+
+    while let Some(current) = device {
+        let bus = match current.subsystem().and_then(|subsystem| subsystem.to_str()) {
+            Some("usb") | Some("usb-serial") => Some(Bus::Usb),
+            Some("pci") => Some(Bus::Pci),
+            Some("platform") => Some(Bus::Platform),
+            Some("pnp") => Some(Bus::Pnp),
+            _ => None,
+        };
+
+        if bus.is_some() {
+            return bus;
+        }
+
+        device = current.parent();
+    }
+
+    None
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use ::device::Device;
+use ::monitor::wire::{Format, Message, Tags};
+
+
+/// An owned copy of a device's properties.
+///
+/// Unlike a `Device`, a snapshot doesn't refer to a libudev context, so it can be created from a
+/// monitor message, built by hand for tests, cloned, and sent between threads.
+///
+/// In udev, the core attributes of a device are also exposed as properties, so a snapshot stores
+/// everything as properties: `DEVPATH`, `SUBSYSTEM`, `DEVTYPE`, `DRIVER`, `DEVNAME`, and so on.
+///
+/// Snapshots built by hand can also be given sysfs attributes and a parent, which makes them
+/// usable as fixtures for code that inspects a device's ancestors.
+///
+/// ## Example
+///
+/// ```
+/// let mut snapshot = libudev::DeviceSnapshot::new();
+///
+/// snapshot.set_property("DEVPATH", "/devices/virtual/tty/tty0").unwrap();
+/// snapshot.set_property("SUBSYSTEM", "tty").unwrap();
+///
+/// assert_eq!(snapshot.sysname().unwrap(), "tty0");
+/// ```
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct DeviceSnapshot {
+    properties: BTreeMap<OsString, OsString>,
+    attributes: BTreeMap<OsString, OsString>,
+    parent: Option<Box<DeviceSnapshot>>,
+}
+
+impl DeviceSnapshot {
+    /// Creates an empty snapshot.
+    pub fn new() -> Self {
+        DeviceSnapshot::default()
+    }
+
+    /// Copies the properties of a device.
+    ///
+    /// The device's devpath, subsystem, devtype, driver, and devnode are recorded in the
+    /// corresponding properties if the device's property list doesn't already contain them.
+    /// Attributes and parents are not copied.
+    pub fn from_device(device: &Device) -> Self {
+        let mut snapshot = DeviceSnapshot::new();
+
+        for property in device.properties() {
+            snapshot.properties.insert(property.name().to_os_string(), property.value().to_os_string());
+        }
+
+        let core = [
+            ("DEVPATH", device.devpath()),
+            ("SUBSYSTEM", device.subsystem()),
+            ("DEVTYPE", device.devtype()),
+            ("DRIVER", device.driver()),
+            ("DEVNAME", device.devnode().map(|p| p.as_os_str())),
+        ];
+
+        for &(name, value) in &core {
+            if let Some(value) = value {
+                snapshot.properties.entry(OsString::from(name)).or_insert_with(|| value.to_os_string());
+            }
+        }
+
+        snapshot
+    }
+
+    /// Copies the properties carried by a monitor message.
+    pub fn from_message(message: &Message) -> Self {
+        DeviceSnapshot {
+            properties: message.properties().map(|(n, v)| (n.to_os_string(), v.to_os_string())).collect(),
+            ..DeviceSnapshot::default()
+        }
+    }
+
+    /// Builds a monitor message that carries the snapshot's properties.
+    ///
+    /// Properties are written in sorted order.
+    pub fn to_message(&self, format: Format) -> Message {
+        let mut message = Message::new(format);
+
+        for (name, value) in &self.properties {
+            // Names and values were validated when they were inserted.
+            message.push_property(name, value).unwrap();
+        }
+
+        message
+    }
+
+    /// Sets the value of a property.
+    ///
+    /// Returns an `InvalidInput` error if the name is empty or contains `=`, or if either string
+    /// contains a NUL byte.
+    pub fn set_property<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, name: T, value: U) -> ::Result<()> {
+        let name = name.as_ref();
+        let value = value.as_ref();
+
+        ::monitor::wire::validate_property(name, value)?;
+
+        self.properties.insert(name.to_os_string(), value.to_os_string());
+
+        Ok(())
+    }
+
+    /// Removes a property, returning its value.
+    pub fn remove_property<T: AsRef<OsStr>>(&mut self, name: T) -> Option<OsString> {
+        self.properties.remove(name.as_ref())
+    }
+
+    /// Retrieves the value of a property.
+    pub fn property_value<T: AsRef<OsStr>>(&self, name: T) -> Option<&OsStr> {
+        self.properties.get(name.as_ref()).map(|v| v.as_os_str())
+    }
+
+    /// Returns an iterator over the snapshot's properties as `(name, value)` pairs, sorted by name.
+    pub fn properties(&self) -> SnapshotProperties<'_> {
+        SnapshotProperties {
+            inner: self.properties.iter(),
+        }
+    }
+
+    /// Sets the value of a sysfs attribute.
+    pub fn set_attribute<T: AsRef<OsStr>, U: AsRef<OsStr>>(&mut self, name: T, value: U) {
+        self.attributes.insert(name.as_ref().to_os_string(), value.as_ref().to_os_string());
+    }
+
+    /// Removes a sysfs attribute, returning its value.
+    pub fn remove_attribute<T: AsRef<OsStr>>(&mut self, name: T) -> Option<OsString> {
+        self.attributes.remove(name.as_ref())
+    }
+
+    /// Retrieves the value of a sysfs attribute.
+    pub fn attribute_value<T: AsRef<OsStr>>(&self, name: T) -> Option<&OsStr> {
+        self.attributes.get(name.as_ref()).map(|v| v.as_os_str())
+    }
+
+    /// Sets the snapshot's parent device.
+    pub fn set_parent(&mut self, parent: DeviceSnapshot) {
+        self.parent = Some(Box::new(parent));
+    }
+
+    /// Returns the snapshot's parent device.
+    pub fn parent(&self) -> Option<&DeviceSnapshot> {
+        self.parent.as_deref()
+    }
+
+    /// Returns the syspath of the device, which is the devpath below `/sys`.
+    pub fn syspath(&self) -> Option<PathBuf> {
+        self.devpath().map(|devpath| Path::new("/sys").join(Path::new(devpath).strip_prefix("/").unwrap_or(Path::new(devpath))))
+    }
+
+    /// Returns the kernel devpath value of the device.
+    pub fn devpath(&self) -> Option<&OsStr> {
+        self.property_value("DEVPATH")
+    }
+
+    /// Returns the path to the device node belonging to the device.
+    pub fn devnode(&self) -> Option<&Path> {
+        self.property_value("DEVNAME").map(Path::new)
+    }
+
+    /// Returns the subsystem name of the device.
+    pub fn subsystem(&self) -> Option<&OsStr> {
+        self.property_value("SUBSYSTEM")
+    }
+
+    /// Returns the kernel device name for the device, which is the last component of its devpath.
+    pub fn sysname(&self) -> Option<&OsStr> {
+        self.devpath().and_then(|devpath| Path::new(devpath).file_name())
+    }
+
+    /// Returns the devtype name of the device.
+    pub fn devtype(&self) -> Option<&OsStr> {
+        self.property_value("DEVTYPE")
+    }
+
+    /// Returns the name of the kernel driver attached to the device.
+    pub fn driver(&self) -> Option<&OsStr> {
+        self.property_value("DRIVER")
+    }
+
+    /// Returns an iterator over the tags in the `TAGS` property.
+    pub fn tags(&self) -> Tags<'_> {
+        Tags::new(self.property_value("TAGS"))
+    }
+
+    /// Checks whether the `TAGS` property contains the given tag.
+    pub fn has_tag<T: AsRef<OsStr>>(&self, tag: T) -> bool {
+        self.tags().any(|t| t == tag.as_ref())
+    }
+}
+
+
+/// Iterator over a snapshot's properties.
+pub struct SnapshotProperties<'a> {
+    inner: ::std::collections::btree_map::Iter<'a, OsString, OsString>,
+}
+
+impl<'a> Iterator for SnapshotProperties<'a> {
+    type Item = (&'a OsStr, &'a OsStr);
+
+    fn next(&mut self) -> Option<(&'a OsStr, &'a OsStr)> {
+        self.inner.next().map(|(name, value)| (name.as_os_str(), value.as_os_str()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,444 @@
+//! Typed views of ALSA sound cards.
+//!
+//! ALSA represents each sound card with a `card*` device in the `sound` subsystem. Its children
+//! are the device nodes of the card, named after the card index `C` and device number `D`:
+//!
+//! * `controlC0` is the control interface of card 0,
+//! * `pcmC0D0p` and `pcmC0D0c` are the playback and capture streams of PCM device 0,
+//! * `midiC0D0` is a raw MIDI device,
+//! * `hwC0D0` is a hardware-dependent interface, such as the HD Audio codec,
+//! * `comprC0D0` is a compressed audio stream.
+//!
+//! The global `timer` and `seq` nodes don't belong to a card. `cards()` groups the nodes of each
+//! card into a `SoundCard`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use libudev::sound::{self, Direction};
+//!
+//! let context = libudev::Context::new().unwrap();
+//!
+//! for card in sound::cards(&context).unwrap() {
+//!     println!("card {:?}: {:?} ({:?})", card.index(), card.id(), card.name());
+//!
+//!     for pcm in card.pcms(Direction::Playback) {
+//!         println!("  hw:{},{} {:?}", pcm.card_index().unwrap(), pcm.device_number().unwrap(), pcm.devnode());
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+use std::fs;
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::ops::Deref;
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::{Enumerator, SortOrder};
+use ::pci::PciDevice;
+use ::usb::UsbDevice;
+
+
+/// Returns the sound cards in the system with their device nodes, ordered by index.
+pub fn cards(context: &Context) -> ::Result<Vec<SoundCard>> {
+    let mut enumerator = Enumerator::new(context)?;
+
+    enumerator.match_subsystem("sound")?;
+
+    let mut cards = BTreeMap::new();
+    let mut nodes = BTreeMap::new();
+
+    for device in enumerator.scan_devices()?.sorted(SortOrder::Sysnum) {
+        let name = match ::util::value_to_str(device.sysname()).and_then(parse_sysname) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        match (name.kind, name.card) {
+            (NodeKind::Card, Some(index)) => {
+                cards.insert(index, device);
+            },
+            (_, Some(index)) => {
+                nodes.entry(index).or_insert_with(Vec::new).push(SoundNode { device, name });
+            },
+            _ => {},
+        }
+    }
+
+    Ok(cards.into_iter().map(|(index, device)| {
+        SoundCard {
+            device,
+            nodes: nodes.remove(&index).unwrap_or_default(),
+        }
+    }).collect())
+}
+
+/// Parses the sysname of an ALSA device.
+///
+/// ```
+/// use libudev::sound::{self, Direction, NodeKind};
+///
+/// let name = sound::parse_sysname("pcmC1D3c").unwrap();
+///
+/// assert_eq!(name.kind(), NodeKind::Pcm(Direction::Capture));
+/// assert_eq!(name.card(), Some(1));
+/// assert_eq!(name.device(), Some(3));
+///
+/// assert_eq!(sound::parse_sysname("controlC0").unwrap().kind(), NodeKind::Control);
+/// assert_eq!(sound::parse_sysname("timer").unwrap().card(), None);
+/// assert_eq!(sound::parse_sysname("pcmC0D0é"), None);
+/// ```
+pub fn parse_sysname(sysname: &str) -> Option<NodeName> {
+    if let Some(index) = sysname.strip_prefix("card") {
+        return Some(NodeName::new(NodeKind::Card, Some(try_number(index)?), None));
+    }
+
+    if let Some(index) = sysname.strip_prefix("controlC") {
+        return Some(NodeName::new(NodeKind::Control, Some(try_number(index)?), None));
+    }
+
+    match sysname {
+        "timer" => return Some(NodeName::new(NodeKind::Timer, None, None)),
+        "seq" => return Some(NodeName::new(NodeKind::Sequencer, None, None)),
+        _ => {},
+    }
+
+    let (prefix, numbers) = sysname.split_at(sysname.find('C')?);
+    let (card, device) = numbers[1..].split_once('D')?;
+
+    let (kind, device) = match prefix {
+        "pcm" => {
+            if let Some(device) = device.strip_suffix('p') {
+                (NodeKind::Pcm(Direction::Playback), device)
+            }
+            else if let Some(device) = device.strip_suffix('c') {
+                (NodeKind::Pcm(Direction::Capture), device)
+            }
+            else {
+                return None;
+            }
+        },
+        "midi" => (NodeKind::Midi, device),
+        "hw" => (NodeKind::Hwdep, device),
+        "compr" => (NodeKind::Compress, device),
+        _ => return None,
+    };
+
+    Some(NodeName::new(kind, Some(try_number(card)?), Some(try_number(device)?)))
+}
+
+fn try_number(digits: &str) -> Option<u32> {
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        digits.parse().ok()
+    }
+    else {
+        None
+    }
+}
+
+
+/// A sound card and its device nodes.
+///
+/// A `SoundCard` wraps the `card*` device of a sound card. It dereferences to the underlying
+/// `Device`.
+#[derive(Clone)]
+pub struct SoundCard {
+    device: Device,
+    nodes: Vec<SoundNode>,
+}
+
+impl SoundCard {
+    /// Finds the sound card that `device` belongs to and loads its device nodes.
+    ///
+    /// `device` can be the `card*` device or one of its nodes. Returns `None` if `device` doesn't
+    /// belong to a sound card.
+    pub fn find(device: &Device) -> ::Result<Option<SoundCard>> {
+        let card = if is_card(device) {
+            device.clone()
+        }
+        else {
+            match device.parent().filter(is_card) {
+                Some(card) => card,
+                None => return Ok(None),
+            }
+        };
+
+        let children = ::device::children(&card, "sound")?;
+        let nodes = children.into_iter().filter_map(SoundNode::from_device).collect();
+
+        Ok(Some(SoundCard { device: card, nodes }))
+    }
+
+    /// Returns the `card*` device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the `card*` device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Returns the index of the card, e.g., 0 for `card0`.
+    pub fn index(&self) -> Option<u32> {
+        ::util::parse_decimal(self.device.attribute_value("number"))
+            .or_else(|| ::util::value_to_str(self.device.sysname()).and_then(parse_sysname).and_then(|name| name.card))
+    }
+
+    /// Returns the identifier of the card (`id`), e.g., `PCH` or `Headset`. ALSA configurations
+    /// refer to cards by this identifier.
+    pub fn id(&self) -> Option<&str> {
+        ::util::value_to_str(self.device.attribute_value("id"))
+    }
+
+    /// Returns the name of the card, e.g., `HDA Intel PCH`, from `/proc/asound/cards`.
+    ///
+    /// sysfs doesn't expose the name. Returns `None` if ALSA's `procfs` interface isn't available.
+    pub fn name(&self) -> Option<String> {
+        let index = self.index()?;
+        let cards = fs::read_to_string("/proc/asound/cards").ok()?;
+
+        // Each card is listed as " 0 [PCH            ]: HDA-Intel - HDA Intel PCH", followed by
+        // a line with its long name.
+        cards.lines().find_map(|line| {
+            let (number, rest) = line.trim_start().split_once(' ')?;
+
+            if number.parse() != Ok(index) {
+                return None;
+            }
+
+            rest.split_once(" - ").map(|(_, name)| name.trim().to_owned())
+        })
+    }
+
+    /// Returns the device nodes of the card, ordered by sysname.
+    pub fn nodes(&self) -> &[SoundNode] {
+        &self.nodes
+    }
+
+    /// Returns the control node of the card.
+    pub fn control(&self) -> Option<&SoundNode> {
+        self.nodes.iter().find(|node| node.kind() == NodeKind::Control)
+    }
+
+    /// Returns the PCM nodes of the card for the given direction.
+    pub fn pcms(&self, direction: Direction) -> Vec<&SoundNode> {
+        self.nodes.iter().filter(|node| node.kind() == NodeKind::Pcm(direction)).collect()
+    }
+
+    /// Returns the device that provides the card, such as a PCI function or a USB interface.
+    pub fn hardware(&self) -> Option<Device> {
+        self.device.parent()
+    }
+
+    /// Returns the USB device that provides the card.
+    pub fn usb_device(&self) -> Option<UsbDevice> {
+        UsbDevice::find(&self.device)
+    }
+
+    /// Returns the PCI device that provides the card, or the USB host controller of its USB
+    /// device.
+    pub fn pci_device(&self) -> Option<PciDevice> {
+        PciDevice::find(&self.device)
+    }
+}
+
+impl Deref for SoundCard {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for SoundCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SoundCard")
+            .field("index", &self.index())
+            .field("id", &self.id())
+            .field("nodes", &self.nodes)
+            .finish()
+    }
+}
+
+
+/// A device node of a sound card.
+///
+/// A `SoundNode` wraps a device in the `sound` subsystem together with its parsed sysname. It
+/// dereferences to the underlying `Device`.
+#[derive(Clone)]
+pub struct SoundNode {
+    device: Device,
+    name: NodeName,
+}
+
+impl SoundNode {
+    /// Creates a view of an ALSA device node.
+    ///
+    /// Returns `None` if `device` isn't in the `sound` subsystem or its sysname isn't recognized.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if device.subsystem() != Some(OsStr::new("sound")) {
+            return None;
+        }
+
+        let name = ::util::value_to_str(device.sysname()).and_then(parse_sysname)?;
+
+        Some(SoundNode { device, name })
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the parsed sysname of the node.
+    pub fn name(&self) -> NodeName {
+        self.name
+    }
+
+    /// Returns the kind of the node.
+    pub fn kind(&self) -> NodeKind {
+        self.name.kind
+    }
+
+    /// Returns the index of the card the node belongs to.
+    pub fn card_index(&self) -> Option<u32> {
+        self.name.card
+    }
+
+    /// Returns the device number of the node within its card.
+    pub fn device_number(&self) -> Option<u32> {
+        self.name.device
+    }
+
+    /// Returns the direction of a PCM node.
+    pub fn direction(&self) -> Option<Direction> {
+        match self.name.kind {
+            NodeKind::Pcm(direction) => Some(direction),
+            _ => None,
+        }
+    }
+
+    /// Returns the subdevice numbers of a PCM node, from `/proc/asound`.
+    ///
+    /// Subdevices aren't represented in sysfs. Most PCM devices have a single subdevice 0.
+    /// Returns an empty list for other nodes or if ALSA's `procfs` interface isn't available.
+    pub fn subdevices(&self) -> Vec<u32> {
+        let (card, device, direction) = match (self.name.card, self.name.device, self.direction()) {
+            (Some(card), Some(device), Some(direction)) => (card, device, direction),
+            _ => return Vec::new(),
+        };
+
+        let suffix = match direction {
+            Direction::Playback => 'p',
+            Direction::Capture => 'c',
+        };
+
+        let entries = match fs::read_dir(format!("/proc/asound/card{}/pcm{}{}", card, device, suffix)) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut subdevices: Vec<u32> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().to_str().and_then(|name| name.strip_prefix("sub")).and_then(try_number))
+            .collect();
+
+        subdevices.sort_unstable();
+        subdevices
+    }
+}
+
+impl Deref for SoundNode {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for SoundNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SoundNode")
+            .field("name", &self.name)
+            .field("devnode", &self.device.devnode())
+            .finish()
+    }
+}
+
+
+/// The parsed sysname of an ALSA device.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct NodeName {
+    kind: NodeKind,
+    card: Option<u32>,
+    device: Option<u32>,
+}
+
+impl NodeName {
+    fn new(kind: NodeKind, card: Option<u32>, device: Option<u32>) -> Self {
+        NodeName { kind, card, device }
+    }
+
+    /// Returns the kind of device.
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// Returns the card index, or `None` for global devices.
+    pub fn card(&self) -> Option<u32> {
+        self.card
+    }
+
+    /// Returns the device number within the card, or `None` for cards and control nodes.
+    pub fn device(&self) -> Option<u32> {
+        self.device
+    }
+}
+
+/// A kind of ALSA device.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum NodeKind {
+    /// A sound card (`card*`).
+    Card,
+
+    /// The control interface of a card (`controlC*`).
+    Control,
+
+    /// A PCM stream (`pcmC*D*p` or `pcmC*D*c`).
+    Pcm(Direction),
+
+    /// A raw MIDI device (`midiC*D*`).
+    Midi,
+
+    /// A hardware-dependent interface (`hwC*D*`).
+    Hwdep,
+
+    /// A compressed audio stream (`comprC*D*`).
+    Compress,
+
+    /// The global timer (`timer`).
+    Timer,
+
+    /// The global sequencer (`seq`).
+    Sequencer,
+}
+
+/// The direction of a PCM stream.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Direction {
+    /// The stream plays audio (`pcmC*D*p`).
+    Playback,
+
+    /// The stream records audio (`pcmC*D*c`).
+    Capture,
+}
+
+
+fn is_card(device: &Device) -> bool {
+    device.subsystem() == Some(OsStr::new("sound"))
+        && ::util::value_to_str(device.sysname()).and_then(parse_sysname).is_some_and(|name| name.kind == NodeKind::Card)
+}
@@ -0,0 +1,350 @@
+//! The device hierarchy as a tree.
+//!
+//! The kernel arranges devices in a hierarchy: a USB interface belongs to a USB device, which
+//! belongs to a hub, which belongs to a PCI controller, and so on. A device's syspath reflects its
+//! position, so `/sys/devices/pci0000:00/0000:00:14.0` is the parent of
+//! `/sys/devices/pci0000:00/0000:00:14.0/usb1`.
+//!
+//! A `DeviceTree` links the devices found by an enumeration by their syspaths. If a device's
+//! parent wasn't enumerated, the device is attached to its closest enumerated ancestor or becomes
+//! a root.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use libudev::tree::DeviceTree;
+//!
+//! let context = libudev::Context::new().unwrap();
+//! let tree = DeviceTree::scan(&context).unwrap();
+//!
+//! // Prints the whole hierarchy, like `lsusb -t` or `lsblk`.
+//! print!("{}", tree);
+//!
+//! // Prints the chain of devices from a TTY to the root of the hierarchy.
+//! if let Some(node) = tree.iter().find(|node| node.device().sysname().is_some_and(|name| name == "ttyUSB0")) {
+//!     for ancestor in node.ancestors() {
+//!         println!("{:?}", ancestor.device().syspath());
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+use std::slice;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use ::context::Context;
+use ::device::Device;
+use ::enumerator::Enumerator;
+
+
+/// A tree of devices linked by their position in sysfs.
+pub struct DeviceTree {
+    nodes: Vec<NodeData>,
+    roots: Vec<usize>,
+    index: HashMap<PathBuf, usize>,
+}
+
+struct NodeData {
+    device: Device,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+impl DeviceTree {
+    /// Scans `/sys` for all devices and builds a tree of them.
+    pub fn scan(context: &Context) -> ::Result<DeviceTree> {
+        let mut enumerator = Enumerator::new(context)?;
+
+        DeviceTree::from_enumerator(&mut enumerator)
+    }
+
+    /// Builds a tree of the devices found by an enumerator.
+    pub fn from_enumerator(enumerator: &mut Enumerator) -> ::Result<DeviceTree> {
+        Ok(DeviceTree::from_devices(enumerator.scan_devices()?))
+    }
+
+    /// Builds a tree of the given devices.
+    ///
+    /// Devices without a syspath are skipped, and devices with the same syspath are only added
+    /// once. Children are ordered by syspath.
+    pub fn from_devices<I: IntoIterator<Item = Device>>(devices: I) -> DeviceTree {
+        let mut devices: Vec<Device> = devices.into_iter().filter(|d| d.syspath().is_some()).collect();
+
+        // Comparing syspaths component by component places parents before their children.
+        devices.sort_by(|a, b| a.syspath().cmp(&b.syspath()));
+        devices.dedup_by(|a, b| a.syspath() == b.syspath());
+
+        let mut tree = DeviceTree {
+            nodes: Vec::with_capacity(devices.len()),
+            roots: Vec::new(),
+            index: HashMap::with_capacity(devices.len()),
+        };
+
+        for device in devices {
+            let syspath = device.syspath().unwrap().to_path_buf();
+            let parent = syspath.ancestors().skip(1).find_map(|path| tree.index.get(path).cloned());
+            let index = tree.nodes.len();
+
+            match parent {
+                Some(parent) => tree.nodes[parent].children.push(index),
+                None => tree.roots.push(index),
+            }
+
+            tree.nodes.push(NodeData {
+                device,
+                parent,
+                children: Vec::new(),
+            });
+
+            tree.index.insert(syspath, index);
+        }
+
+        tree
+    }
+
+    /// Returns the number of devices in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the tree doesn't contain any devices.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the device with the given syspath.
+    pub fn get<T: AsRef<Path>>(&self, syspath: T) -> Option<Node<'_>> {
+        self.index.get(syspath.as_ref()).map(|&index| self.node(index))
+    }
+
+    /// Returns an iterator over the devices that don't have a parent in the tree.
+    pub fn roots(&self) -> Nodes<'_> {
+        Nodes {
+            tree: self,
+            indices: self.roots.iter(),
+        }
+    }
+
+    /// Returns an iterator over all devices in depth-first order. Each device is followed by its
+    /// subtree.
+    pub fn iter(&self) -> Subtree<'_> {
+        Subtree {
+            tree: self,
+            stack: self.roots.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// Renders the tree as text, using `label` to describe each device.
+    ///
+    /// Each device is printed on its own line below its parent, with lines drawn between them in
+    /// the style of `lsblk`.
+    pub fn render_with<F: FnMut(&Device) -> String>(&self, mut label: F) -> String {
+        let mut output = String::new();
+
+        for root in self.roots() {
+            root.render_into(&mut output, "", "", &mut label);
+        }
+
+        output
+    }
+
+    fn node(&self, index: usize) -> Node<'_> {
+        Node {
+            tree: self,
+            index,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a DeviceTree {
+    type Item = Node<'a>;
+    type IntoIter = Subtree<'a>;
+
+    fn into_iter(self) -> Subtree<'a> {
+        self.iter()
+    }
+}
+
+/// Renders the tree with `render_with()`, labelling each device with its sysname, subsystem,
+/// driver, and device node.
+impl fmt::Display for DeviceTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render_with(default_label))
+    }
+}
+
+impl fmt::Debug for DeviceTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DeviceTree")
+            .field("len", &self.len())
+            .field("roots", &self.roots.len())
+            .finish()
+    }
+}
+
+
+/// A device in a `DeviceTree`.
+#[derive(Clone,Copy)]
+pub struct Node<'a> {
+    tree: &'a DeviceTree,
+    index: usize,
+}
+
+impl<'a> Node<'a> {
+    /// Returns the device.
+    pub fn device(&self) -> &'a Device {
+        &self.tree.nodes[self.index].device
+    }
+
+    /// Returns the device's parent in the tree.
+    pub fn parent(&self) -> Option<Node<'a>> {
+        self.tree.nodes[self.index].parent.map(|index| self.tree.node(index))
+    }
+
+    /// Returns an iterator over the device's children.
+    pub fn children(&self) -> Nodes<'a> {
+        Nodes {
+            tree: self.tree,
+            indices: self.tree.nodes[self.index].children.iter(),
+        }
+    }
+
+    /// Returns an iterator over the path from the device to the root of its tree, starting with
+    /// the device itself.
+    pub fn ancestors(&self) -> Ancestors<'a> {
+        Ancestors {
+            next: Some(*self),
+        }
+    }
+
+    /// Returns an iterator over the device and all of its descendants in depth-first order.
+    pub fn subtree(&self) -> Subtree<'a> {
+        Subtree {
+            tree: self.tree,
+            stack: vec![self.index],
+        }
+    }
+
+    /// Returns the number of ancestors of the device in the tree. Roots have a depth of zero.
+    pub fn depth(&self) -> usize {
+        self.ancestors().count() - 1
+    }
+
+    /// Checks whether `other` is the device itself or one of its descendants.
+    pub fn contains(&self, other: &Node<'_>) -> bool {
+        ::std::ptr::eq(self.tree, other.tree) && other.ancestors().any(|node| node.index == self.index)
+    }
+
+    /// Renders the device's subtree as text. See `DeviceTree::render_with()`.
+    pub fn render_with<F: FnMut(&Device) -> String>(&self, mut label: F) -> String {
+        let mut output = String::new();
+        self.render_into(&mut output, "", "", &mut label);
+        output
+    }
+
+    fn render_into(&self, output: &mut String, first_prefix: &str, prefix: &str, label: &mut dyn FnMut(&Device) -> String) {
+        output.push_str(first_prefix);
+        output.push_str(&label(self.device()));
+        output.push('\n');
+
+        let count = self.tree.nodes[self.index].children.len();
+
+        for (i, child) in self.children().enumerate() {
+            if i + 1 < count {
+                child.render_into(output, &format!("{}├─", prefix), &format!("{}│ ", prefix), label);
+            }
+            else {
+                child.render_into(output, &format!("{}└─", prefix), &format!("{}  ", prefix), label);
+            }
+        }
+    }
+}
+
+/// Renders the node's subtree with the same labels as `DeviceTree`.
+impl<'a> fmt::Display for Node<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render_with(default_label))
+    }
+}
+
+impl<'a> fmt::Debug for Node<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("syspath", &self.device().syspath())
+            .finish()
+    }
+}
+
+
+/// Iterator over a list of nodes, such as the children of a node.
+pub struct Nodes<'a> {
+    tree: &'a DeviceTree,
+    indices: slice::Iter<'a, usize>,
+}
+
+impl<'a> Iterator for Nodes<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        self.indices.next().map(|&index| self.tree.node(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+/// Iterator over a node and its ancestors.
+pub struct Ancestors<'a> {
+    next: Option<Node<'a>>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        let node = self.next.take();
+        self.next = node.and_then(|node| node.parent());
+        node
+    }
+}
+
+/// Depth-first iterator over a subtree.
+pub struct Subtree<'a> {
+    tree: &'a DeviceTree,
+    stack: Vec<usize>,
+}
+
+impl<'a> Iterator for Subtree<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        let index = self.stack.pop()?;
+
+        self.stack.extend(self.tree.nodes[index].children.iter().rev());
+
+        Some(self.tree.node(index))
+    }
+}
+
+
+fn default_label(device: &Device) -> String {
+    let mut label = device.sysname().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let details: Vec<String> = [device.subsystem(), device.driver()].iter()
+        .flatten()
+        .map(|value| value.to_string_lossy().into_owned())
+        .collect();
+
+    if !details.is_empty() {
+        label.push_str(&format!(" ({})", details.join(", ")));
+    }
+
+    if let Some(devnode) = device.devnode() {
+        label.push_str(&format!(" {}", devnode.display()));
+    }
+
+    label
+}
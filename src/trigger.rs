@@ -0,0 +1,301 @@
+use std::fmt;
+use std::ptr;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use std::os::unix::io::AsRawFd;
+
+use ::context::Context;
+use ::enumerator::Enumerator;
+use ::monitor::Monitor;
+
+
+/// Actions that can be requested from the kernel with a synthetic uevent.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Action {
+    /// Requests an `add` event.
+    Add,
+
+    /// Requests a `remove` event.
+    Remove,
+
+    /// Requests a `change` event. This is the action used to re-run udev rules for a device.
+    Change,
+
+    /// Requests a `move` event.
+    Move,
+
+    /// Requests an `online` event.
+    Online,
+
+    /// Requests an `offline` event.
+    Offline,
+
+    /// Requests a `bind` event.
+    Bind,
+
+    /// Requests an `unbind` event.
+    Unbind,
+}
+
+impl Action {
+    /// Returns the name of the action as written to a device's `uevent` attribute.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Action::Add => "add",
+            Action::Remove => "remove",
+            Action::Change => "change",
+            Action::Move => "move",
+            Action::Online => "online",
+            Action::Offline => "offline",
+            Action::Bind => "bind",
+            Action::Unbind => "unbind",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses an action name. Unknown names are reported as an `InvalidInput` error.
+impl FromStr for Action {
+    type Err = ::Error;
+
+    fn from_str(s: &str) -> ::Result<Action> {
+        match s {
+            "add" => Ok(Action::Add),
+            "remove" => Ok(Action::Remove),
+            "change" => Ok(Action::Change),
+            "move" => Ok(Action::Move),
+            "online" => Ok(Action::Online),
+            "offline" => Ok(Action::Offline),
+            "bind" => Ok(Action::Bind),
+            "unbind" => Ok(Action::Unbind),
+            _ => Err(::error::from_errno(-::libc::EINVAL)),
+        }
+    }
+}
+
+
+/// A UUID that identifies a synthetic uevent.
+///
+/// The kernel reports the UUID in the `SYNTH_UUID` property of the resulting event, which allows
+/// the event to be told apart from events that were triggered by someone else.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Uuid {
+    bytes: [u8; 16],
+}
+
+impl Uuid {
+    /// Generates a random (version 4) UUID.
+    pub fn new_v4() -> ::Result<Uuid> {
+        let mut bytes = [0; 16];
+
+        let result = File::open("/dev/urandom").and_then(|mut file| file.read_exact(&mut bytes));
+
+        if let Err(err) = result {
+            return Err(::error::from_errno(-err.raw_os_error().unwrap_or(::libc::EIO)));
+        }
+
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+        Ok(Uuid { bytes })
+    }
+
+    /// Returns the UUID's bytes.
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.bytes
+    }
+}
+
+/// Formats the UUID in its hyphenated form, e.g., `c7a3d8d2-64b0-4c4e-9d3a-0e6f8f1b2a44`.
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, byte) in self.bytes.iter().enumerate() {
+            if i == 4 || i == 6 || i == 8 || i == 10 {
+                f.write_str("-")?;
+            }
+
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Triggers synthetic uevents for every device found by an `Enumerator`.
+///
+/// This is the equivalent of `udevadm trigger`. With `settle()`, the trigger waits until `udevd`
+/// has finished processing each of the events that it caused, like `udevadm trigger --settle`.
+///
+/// ## Example
+///
+/// This example re-runs the udev rules for all TTY devices and waits up to five seconds for
+/// `udevd` to process them:
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use libudev::{Action, Trigger};
+///
+/// let context = libudev::Context::new().unwrap();
+/// let mut enumerator = libudev::Enumerator::new(&context).unwrap();
+/// enumerator.match_subsystem("tty").unwrap();
+///
+/// let mut trigger = Trigger::new(Action::Change);
+/// trigger.settle(Duration::from_secs(5));
+///
+/// for device in trigger.run(&context, &mut enumerator).unwrap() {
+///     println!("{:?} settled={}", device.syspath(), device.is_settled());
+/// }
+/// ```
+#[derive(Debug,Clone)]
+pub struct Trigger {
+    action: Action,
+    settle: Option<Duration>,
+}
+
+impl Trigger {
+    /// Creates a trigger for the given action.
+    pub fn new(action: Action) -> Self {
+        Trigger {
+            action,
+            settle: None,
+        }
+    }
+
+    /// Waits up to `timeout` for `udevd` to process the triggered events.
+    pub fn settle(&mut self, timeout: Duration) {
+        self.settle = Some(timeout);
+    }
+
+    /// Triggers an event for each device found by the enumerator.
+    ///
+    /// Devices that disappear before their event can be triggered are skipped. Any other error
+    /// stops the trigger. When settling, this method returns once every triggered event has been
+    /// processed or the timeout has expired, whichever comes first.
+    pub fn run(&self, context: &Context, enumerator: &mut Enumerator) -> ::Result<Vec<Triggered>> {
+        // The monitor must be listening before the first event is triggered so that no events are
+        // missed.
+        let mut socket = match self.settle {
+            Some(_) => Some(Monitor::new(context)?.listen()?),
+            None => None,
+        };
+
+        let mut triggered = Vec::new();
+
+        for mut device in enumerator.scan_devices()? {
+            let syspath = match device.syspath() {
+                Some(syspath) => syspath.to_path_buf(),
+                None => continue,
+            };
+
+            match device.trigger(self.action) {
+                Ok(uuid) => {
+                    triggered.push(Triggered {
+                        syspath,
+                        uuid,
+                        settled: false,
+                    });
+                },
+                Err(ref err) if is_gone(err) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        if let (Some(socket), Some(timeout)) = (socket.as_mut(), self.settle) {
+            let mut pending: HashMap<String, usize> = triggered.iter()
+                .enumerate()
+                .map(|(i, t)| (t.uuid.to_string(), i))
+                .collect();
+
+            let deadline = Instant::now() + timeout;
+
+            while !pending.is_empty() {
+                let now = Instant::now();
+
+                if now >= deadline || !wait_readable(socket.as_raw_fd(), deadline - now)? {
+                    break;
+                }
+
+                while let Some(event) = socket.receive_event() {
+                    let index = event.property_value("SYNTH_UUID")
+                        .and_then(|uuid| uuid.to_str())
+                        .and_then(|uuid| pending.remove(uuid));
+
+                    if let Some(index) = index {
+                        triggered[index].settled = true;
+                    }
+                }
+            }
+        }
+
+        Ok(triggered)
+    }
+}
+
+
+/// A device for which an event was triggered.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Triggered {
+    syspath: PathBuf,
+    uuid: Uuid,
+    settled: bool,
+}
+
+impl Triggered {
+    /// Returns the syspath of the device.
+    pub fn syspath(&self) -> &Path {
+        &self.syspath
+    }
+
+    /// Returns the UUID of the triggered event.
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    /// Returns `true` if `udevd` was observed processing the event while settling.
+    pub fn is_settled(&self) -> bool {
+        self.settled
+    }
+}
+
+
+fn is_gone(err: &::Error) -> bool {
+    match err.kind() {
+        ::ErrorKind::Io(::std::io::ErrorKind::NotFound) => true,
+        _ => err.raw_os_error() == ::libc::ENODEV,
+    }
+}
+
+/// Waits until `fd` is readable. Returns `false` if the timeout expires first.
+fn wait_readable(fd: ::libc::c_int, timeout: Duration) -> ::Result<bool> {
+    let mut pollfd = ::libc::pollfd {
+        fd,
+        events: ::libc::POLLIN,
+        revents: 0,
+    };
+
+    let timeout = ::libc::timespec {
+        tv_sec: timeout.as_secs() as ::libc::time_t,
+        tv_nsec: timeout.subsec_nanos() as ::libc::c_long,
+    };
+
+    let result = unsafe { ::libc::ppoll(&mut pollfd, 1, &timeout, ptr::null()) };
+
+    match result {
+        n if n > 0 => Ok(true),
+        0 => Ok(false),
+        _ if ::std::io::Error::last_os_error().kind() == ::std::io::ErrorKind::Interrupted => Ok(true),
+        _ => Err(::error::last_os_error()),
+    }
+}
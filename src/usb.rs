@@ -0,0 +1,440 @@
+//! Typed views of USB devices and interfaces.
+//!
+//! The kernel represents a USB device with two kinds of devices in the `usb` subsystem: a
+//! `usb_device` for the device itself and a `usb_interface` for each of the interfaces of its
+//! active configuration. Device nodes such as TTYs, input devices, and `hidraw` nodes belong to
+//! an interface, which belongs to the device.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use libudev::usb::UsbDevice;
+//!
+//! let context = libudev::Context::new().unwrap();
+//! let tty = libudev::Device::from_syspath(&context, Path::new("/sys/class/tty/ttyUSB0")).unwrap();
+//!
+//! if let Some(usb) = UsbDevice::find(&tty) {
+//!     println!("{:04x}:{:04x} at port {}", usb.vendor_id().unwrap(), usb.product_id().unwrap(), usb.port_path().unwrap());
+//!
+//!     for interface in usb.interfaces().unwrap() {
+//!         println!("  interface {:?}: class {:?}", interface.interface_number(), interface.class());
+//!     }
+//! }
+//! ```
+
+use std::error;
+use std::fmt;
+
+use std::ffi::OsStr;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use ::device::Device;
+
+
+/// A USB device.
+///
+/// A `UsbDevice` wraps a device in the `usb` subsystem with the devtype `usb_device` and provides
+/// typed access to its attributes. It dereferences to the underlying `Device`.
+#[derive(Clone)]
+pub struct UsbDevice {
+    device: Device,
+}
+
+impl UsbDevice {
+    /// Creates a USB device view of `device`.
+    ///
+    /// Returns `None` if `device` isn't a USB device.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if is_usb_device(&device, "usb_device") {
+            Some(UsbDevice { device })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Finds the USB device that `device` belongs to.
+    ///
+    /// Returns `device` itself if it's a USB device, otherwise its closest USB device ancestor.
+    /// This finds the USB device behind a TTY, input device, `hidraw` node, or USB interface.
+    pub fn find(device: &Device) -> Option<Self> {
+        if is_usb_device(device, "usb_device") {
+            return Some(UsbDevice { device: device.clone() });
+        }
+
+        device.parent_with_subsystem_devtype("usb", "usb_device").map(|device| UsbDevice { device })
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the underlying device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Returns the vendor ID (`idVendor`).
+    pub fn vendor_id(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("idVendor"))
+    }
+
+    /// Returns the product ID (`idProduct`).
+    pub fn product_id(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("idProduct"))
+    }
+
+    /// Returns the device release number in binary-coded decimal (`bcdDevice`).
+    pub fn device_release(&self) -> Option<u16> {
+        ::util::parse_hex(self.device.attribute_value("bcdDevice"))
+    }
+
+    /// Returns the manufacturer string reported by the device.
+    pub fn manufacturer(&self) -> Option<&str> {
+        ::util::value_to_str(self.device.attribute_value("manufacturer"))
+    }
+
+    /// Returns the product string reported by the device.
+    pub fn product(&self) -> Option<&str> {
+        ::util::value_to_str(self.device.attribute_value("product"))
+    }
+
+    /// Returns the serial number string reported by the device.
+    pub fn serial(&self) -> Option<&str> {
+        ::util::value_to_str(self.device.attribute_value("serial"))
+    }
+
+    /// Returns the number of the bus the device is connected to (`busnum`).
+    pub fn bus_number(&self) -> Option<u16> {
+        ::util::parse_decimal(self.device.attribute_value("busnum"))
+    }
+
+    /// Returns the address of the device on its bus (`devnum`).
+    ///
+    /// This is the USB device address, not the device number of the device node. See
+    /// `Device::devnum()` for the latter.
+    pub fn device_number(&self) -> Option<u16> {
+        ::util::parse_decimal(self.device.attribute_value("devnum"))
+    }
+
+    /// Returns the speed at which the device is operating.
+    pub fn speed(&self) -> Option<Speed> {
+        ::util::value_to_str(self.device.attribute_value("speed")).and_then(Speed::from_mbps)
+    }
+
+    /// Returns the device class code (`bDeviceClass`).
+    pub fn device_class(&self) -> Option<u8> {
+        ::util::parse_hex(self.device.attribute_value("bDeviceClass"))
+    }
+
+    /// Returns the device subclass code (`bDeviceSubClass`).
+    pub fn device_subclass(&self) -> Option<u8> {
+        ::util::parse_hex(self.device.attribute_value("bDeviceSubClass"))
+    }
+
+    /// Returns the device protocol code (`bDeviceProtocol`).
+    pub fn device_protocol(&self) -> Option<u8> {
+        ::util::parse_hex(self.device.attribute_value("bDeviceProtocol"))
+    }
+
+    /// Returns the number of configurations the device supports (`bNumConfigurations`).
+    pub fn num_configurations(&self) -> Option<u8> {
+        ::util::parse_decimal(self.device.attribute_value("bNumConfigurations"))
+    }
+
+    /// Returns the path of ports from the root hub to the device.
+    pub fn port_path(&self) -> Option<PortPath> {
+        self.device.sysname().and_then(|sysname| sysname.to_str()).and_then(|sysname| sysname.parse().ok())
+    }
+
+    /// Returns the interfaces of the device's active configuration, ordered by sysname.
+    pub fn interfaces(&self) -> ::Result<Vec<UsbInterface>> {
+        let children = ::device::children(&self.device, "usb")?;
+
+        Ok(children.into_iter().filter_map(UsbInterface::from_device).collect())
+    }
+}
+
+impl Deref for UsbDevice {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for UsbDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UsbDevice")
+            .field("syspath", &self.device.syspath())
+            .field("vendor_id", &self.vendor_id())
+            .field("product_id", &self.product_id())
+            .finish()
+    }
+}
+
+
+/// An interface of a USB device.
+///
+/// A `UsbInterface` wraps a device in the `usb` subsystem with the devtype `usb_interface`. It
+/// dereferences to the underlying `Device`.
+#[derive(Clone)]
+pub struct UsbInterface {
+    device: Device,
+}
+
+impl UsbInterface {
+    /// Creates a USB interface view of `device`.
+    ///
+    /// Returns `None` if `device` isn't a USB interface.
+    pub fn from_device(device: Device) -> Option<Self> {
+        if is_usb_device(&device, "usb_interface") {
+            Some(UsbInterface { device })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Finds the USB interface that `device` belongs to.
+    ///
+    /// Returns `device` itself if it's a USB interface, otherwise its closest USB interface
+    /// ancestor.
+    pub fn find(device: &Device) -> Option<Self> {
+        if is_usb_device(device, "usb_interface") {
+            return Some(UsbInterface { device: device.clone() });
+        }
+
+        device.parent_with_subsystem_devtype("usb", "usb_interface").map(|device| UsbInterface { device })
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Consumes the view and returns the underlying device.
+    pub fn into_device(self) -> Device {
+        self.device
+    }
+
+    /// Returns the interface number (`bInterfaceNumber`).
+    pub fn interface_number(&self) -> Option<u8> {
+        ::util::parse_hex(self.device.attribute_value("bInterfaceNumber"))
+    }
+
+    /// Returns the alternate setting of the interface (`bAlternateSetting`).
+    pub fn alternate_setting(&self) -> Option<u8> {
+        ::util::parse_decimal(self.device.attribute_value("bAlternateSetting"))
+    }
+
+    /// Returns the interface class code (`bInterfaceClass`).
+    pub fn class(&self) -> Option<u8> {
+        ::util::parse_hex(self.device.attribute_value("bInterfaceClass"))
+    }
+
+    /// Returns the interface subclass code (`bInterfaceSubClass`).
+    pub fn subclass(&self) -> Option<u8> {
+        ::util::parse_hex(self.device.attribute_value("bInterfaceSubClass"))
+    }
+
+    /// Returns the interface protocol code (`bInterfaceProtocol`).
+    pub fn protocol(&self) -> Option<u8> {
+        ::util::parse_hex(self.device.attribute_value("bInterfaceProtocol"))
+    }
+
+    /// Returns the number of endpoints of the interface (`bNumEndpoints`).
+    pub fn num_endpoints(&self) -> Option<u8> {
+        ::util::parse_hex(self.device.attribute_value("bNumEndpoints"))
+    }
+
+    /// Returns the interface string reported by the device.
+    pub fn interface(&self) -> Option<&str> {
+        ::util::value_to_str(self.device.attribute_value("interface"))
+    }
+
+    /// Returns the USB device that the interface belongs to.
+    pub fn usb_device(&self) -> Option<UsbDevice> {
+        self.device.parent().and_then(UsbDevice::from_device)
+    }
+}
+
+impl Deref for UsbInterface {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+impl fmt::Debug for UsbInterface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UsbInterface")
+            .field("syspath", &self.device.syspath())
+            .field("interface_number", &self.interface_number())
+            .field("class", &self.class())
+            .finish()
+    }
+}
+
+
+/// The speed at which a USB device operates.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub enum Speed {
+    /// Low speed (1.5 Mbit/s, USB 1.0).
+    Low,
+
+    /// Full speed (12 Mbit/s, USB 1.1).
+    Full,
+
+    /// High speed (480 Mbit/s, USB 2.0).
+    High,
+
+    /// SuperSpeed (5 Gbit/s, USB 3.0).
+    Super,
+
+    /// SuperSpeed+ (10 Gbit/s, USB 3.1).
+    SuperPlus,
+
+    /// SuperSpeed+ with two lanes (20 Gbit/s, USB 3.2).
+    SuperPlusX2,
+}
+
+impl Speed {
+    /// Returns the signalling rate in Mbit/s, as reported by the `speed` attribute.
+    pub fn mbps(self) -> f64 {
+        match self {
+            Speed::Low => 1.5,
+            Speed::Full => 12.0,
+            Speed::High => 480.0,
+            Speed::Super => 5000.0,
+            Speed::SuperPlus => 10000.0,
+            Speed::SuperPlusX2 => 20000.0,
+        }
+    }
+
+    fn from_mbps(value: &str) -> Option<Speed> {
+        match value {
+            "1.5" => Some(Speed::Low),
+            "12" => Some(Speed::Full),
+            "480" => Some(Speed::High),
+            "5000" => Some(Speed::Super),
+            "10000" => Some(Speed::SuperPlus),
+            "20000" => Some(Speed::SuperPlusX2),
+            _ => None,
+        }
+    }
+}
+
+
+/// The location of a USB device: its bus and the ports leading from the root hub to it.
+///
+/// Port paths are written the way the kernel names USB devices, e.g., `1-1.4.2` for the device on
+/// port 2 of the hub on port 4 of the hub on port 1 of bus 1. A root hub, such as `usb1`, has an
+/// empty list of ports.
+///
+/// ```
+/// use libudev::usb::PortPath;
+///
+/// let path: PortPath = "1-1.4.2".parse().unwrap();
+///
+/// assert_eq!(path.bus(), 1);
+/// assert_eq!(path.ports(), &[1, 4, 2]);
+/// assert_eq!(path.to_string(), "1-1.4.2");
+/// assert_eq!("usb3".parse::<PortPath>().unwrap().ports(), &[] as &[u8]);
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct PortPath {
+    bus: u16,
+    ports: Vec<u8>,
+}
+
+impl PortPath {
+    /// Creates a port path from a bus number and a list of ports.
+    pub fn new(bus: u16, ports: Vec<u8>) -> Self {
+        PortPath { bus, ports }
+    }
+
+    /// Returns the bus number.
+    pub fn bus(&self) -> u16 {
+        self.bus
+    }
+
+    /// Returns the ports from the root hub to the device.
+    pub fn ports(&self) -> &[u8] {
+        &self.ports
+    }
+
+    /// Checks whether the path belongs to a root hub.
+    pub fn is_root_hub(&self) -> bool {
+        self.ports.is_empty()
+    }
+
+    /// Returns the path of the hub the device is connected to, or `None` for a root hub.
+    pub fn parent(&self) -> Option<PortPath> {
+        let (_, ports) = self.ports.split_last()?;
+
+        Some(PortPath::new(self.bus, ports.to_vec()))
+    }
+}
+
+impl fmt::Display for PortPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ports.is_empty() {
+            return write!(f, "usb{}", self.bus);
+        }
+
+        write!(f, "{}-", self.bus)?;
+
+        for (i, port) in self.ports.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+
+            write!(f, "{}", port)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for PortPath {
+    type Err = ParsePortPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(bus) = s.strip_prefix("usb") {
+            return bus.parse().map(|bus| PortPath::new(bus, Vec::new())).map_err(|_| ParsePortPathError);
+        }
+
+        let (bus, ports) = match s.split_once('-') {
+            Some(parts) => parts,
+            None => return Err(ParsePortPathError),
+        };
+
+        let bus = bus.parse().map_err(|_| ParsePortPathError)?;
+        let ports = ports.split('.').map(|port| port.parse()).collect::<Result<Vec<u8>, _>>().map_err(|_| ParsePortPathError)?;
+
+        Ok(PortPath::new(bus, ports))
+    }
+}
+
+/// An error returned when a string isn't a valid USB port path.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct ParsePortPathError;
+
+impl fmt::Display for ParsePortPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid USB port path")
+    }
+}
+
+impl error::Error for ParsePortPathError {}
+
+
+fn is_usb_device(device: &Device, devtype: &str) -> bool {
+    device.subsystem() == Some(OsStr::new("usb")) && device.devtype() == Some(OsStr::new(devtype))
+}
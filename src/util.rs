@@ -1,6 +1,9 @@
 use std::slice;
+use std::str;
+use std::convert::TryFrom;
 use std::ffi::{CString, OsStr};
 use std::path::Path;
+use std::str::FromStr;
 
 use libc::{c_int, c_char};
 
@@ -44,3 +47,38 @@ pub fn errno_to_result(errno: c_int) -> ::Result<()> {
         e => Err(::error::from_errno(e)),
     }
 }
+
+/// Matches a string against a shell-style glob pattern with the same rules as libudev.
+pub fn fnmatch<T: AsRef<OsStr>, U: AsRef<OsStr>>(pattern: T, string: U) -> bool {
+    match (CString::new(pattern.as_ref().as_bytes()), CString::new(string.as_ref().as_bytes())) {
+        (Ok(pattern), Ok(string)) => unsafe { ::libc::fnmatch(pattern.as_ptr(), string.as_ptr(), 0) == 0 },
+        _ => false,
+    }
+}
+
+/// Converts an attribute or property value to a string with surrounding whitespace removed.
+pub fn value_to_str(value: Option<&OsStr>) -> Option<&str> {
+    value.and_then(|value| str::from_utf8(value.as_bytes()).ok()).map(|value| value.trim())
+}
+
+/// Parses an attribute or property value as a decimal number.
+pub fn parse_decimal<T: FromStr>(value: Option<&OsStr>) -> Option<T> {
+    value_to_str(value).and_then(|value| value.parse().ok())
+}
+
+/// Parses an attribute or property value as a hexadecimal number, with or without a `0x` prefix.
+pub fn parse_hex<T: TryFrom<u64>>(value: Option<&OsStr>) -> Option<T> {
+    let value = value_to_str(value)?;
+    let digits = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")).unwrap_or(value);
+
+    u64::from_str_radix(digits, 16).ok().and_then(|value| T::try_from(value).ok())
+}
+
+/// Parses an attribute or property value as a boolean flag, where `1` means `true`.
+pub fn parse_flag(value: Option<&OsStr>) -> Option<bool> {
+    match value_to_str(value) {
+        Some("1") => Some(true),
+        Some("0") => Some(false),
+        _ => None,
+    }
+}